@@ -56,7 +56,7 @@ impl Sandbox for CardVisualizer {
         let player_group = setup_players(Some(3));
 
         // main logic encapsulated in Game struct
-        let mut game_handler = GameMaster::new(Game::new(), player_group);
+        let mut game_handler = GameMaster::new(Game::new(None), player_group);
     
         // game top level function
         game_handler.init().unwrap();
@@ -94,7 +94,9 @@ impl Sandbox for CardVisualizer {
             Message::StartNewHand(starting_next_game) => {
                 self.game_over = starting_next_game;
                 
-                self.game_state = GameMaster::new(Game::new(), self.game_state.clone().players);
+                let next_seed = self.game_state.next_hand_seed();
+                let variant = self.game_state.gamestate.variant;
+                self.game_state = GameMaster::new(Game::new_variant(next_seed, variant), self.game_state.clone().players);
                 self.game_state.init().unwrap();
                 let hero = self.game_state.clone().gamestate.get_player("phil").unwrap();
 
@@ -113,7 +115,7 @@ impl Sandbox for CardVisualizer {
         }
     }
 
-    fn view(&self) -> Element<Self::Message> {
+    fn view(&self) -> Element<'_, Self::Message> {
         let mut table_svg: Vec<String> = Vec::new();
         if let Some(tbl_cards) = self.game_state.gamestate.table_cards.clone() {
             table_svg = Game::select_cards_svg(tbl_cards);
@@ -143,7 +145,7 @@ fn svg_path_setup(card_file: String) -> Svg {
 fn add_player_hands(hand_group: Vec<Vec<String>>) -> Vec<(Svg, Svg)> {
     let mut card_images: Vec<(Svg, Svg)> = Vec::new();
     for hand in hand_group {
-        if let (Some(c1), Some(c2)) = (hand.get(0), hand.get(1)) {
+        if let (Some(c1), Some(c2)) = (hand.first(), hand.get(1)) {
             let cimg1: Svg = Svg::new(Handle::from_path(c1));
             let cimg2: Svg = Svg::new(Handle::from_path(c2));
             card_images.push((cimg1, cimg2));
@@ -153,7 +155,7 @@ fn add_player_hands(hand_group: Vec<Vec<String>>) -> Vec<(Svg, Svg)> {
     
     
 
-    return card_images
+    card_images
 }
 fn container_builder(player_hand: Vec<String>, group_cards: Vec<Vec<String>>, table_cards: Vec<String>) -> Element<'static, Message> {
     // functions triggered through Messages
@@ -181,13 +183,13 @@ fn container_builder(player_hand: Vec<String>, group_cards: Vec<Vec<String>>, ta
     let mut t4: Svg = Svg::new(Handle::from_path(""));
     let mut t5: Svg = Svg::new(Handle::from_path(""));
 
-    if let (Some(_card1), Some(_card2)) = ( pc.get(0), pc.get(1) ) {
+    if let (Some(_card1), Some(_card2)) = ( pc.first(), pc.get(1) ) {
         p1 = svg_path_setup(_card1.to_owned());
         p2 = svg_path_setup(_card2.to_owned());
 
     }
     if let ( Some(_card1), Some(_card2), Some(_card3), _card4, _card5 ) = 
-    ( tc.get(0), tc.get(1), tc.get(2), tc.get(3), tc.get(4)) 
+    ( tc.first(), tc.get(1), tc.get(2), tc.get(3), tc.get(4)) 
     {
         
         t1 = svg_path_setup(_card1.to_owned());
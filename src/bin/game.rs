@@ -1,7 +1,28 @@
 #[allow(unused_imports)]
-use holdem::{Game, Player, Hand, GameMaster};
+use holdem::{Game, Player, Hand, GameMaster, Variant};
 use std::error::Error;
+use clap::Parser;
 
+/// optional seed and fixed cards to replay a specific deal
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    /// Seed for the deck shuffle, identical seeds deal identical cards
+    #[arg(short = 's', long = "seed")]
+    seed: Option<u64>,
+    /// Fixed hole cards for the hero, e.g. "AhKd"
+    #[arg(long = "hero")]
+    hero: Option<Hand>,
+    /// Fixed board cards dealt in order, e.g. "Th 9h 8c"
+    #[arg(long = "board")]
+    board: Option<Hand>,
+    /// Play short deck (6 through A) instead of the full 52 cards
+    #[arg(long = "short-deck")]
+    short_deck: bool,
+}
+
+
+const STARTING_STACK: i32 = 1000;
 
 fn setup_players(count: Option<u8>) -> Vec<Player> {
     let mut vp: Vec<Player> = Vec::new();
@@ -20,6 +41,9 @@ fn setup_players(count: Option<u8>) -> Vec<Player> {
         }
        
     }
+    for p in vp.iter_mut() {
+        p.chips = STARTING_STACK;
+    }
     vp
 }
 /*
@@ -37,14 +61,39 @@ wasm GUI
 fn main() -> Result<(), Box< dyn Error>> {
     // let hs = HandScore::new();
     // hs.display_point_values();
+    let args = Args::parse();
     let player_group = setup_players(Some(3));
 
     // main logic encapsulated in Game struct
-    let mut game_handler = GameMaster::new(Game::new(), player_group);
+    let variant = if args.short_deck { Variant::ShortDeck } else { Variant::Holdem };
+    let mut game_handler = GameMaster::new(Game::new_variant(args.seed, variant), player_group);
+    if let Some(hero) = args.hero {
+        game_handler.set_hole_cards(0, hero)?;
+    }
+    if let Some(board) = args.board {
+        game_handler.set_board(board.cards)?;
+    }
 
     // game top level function
     game_handler.init()?;
 
+    // run the board out and settle the pot
+    game_handler.deal_turn_or_river()?;
+    game_handler.deal_turn_or_river()?;
+    // there is no betting round yet, so only the big blind is in for the full amount
+    for seat in game_handler.fold_unmatched() {
+        println!("{} folds", game_handler.players[seat].name.clone().unwrap_or_default());
+    }
+    for award in game_handler.showdown()? {
+        for (seat, share) in award.winners.iter().zip(&award.shares) {
+            let name = game_handler.players[*seat].name.clone().unwrap_or_default();
+            match &award.winning_hand {
+                Some(best) => println!("{} wins {} with {}", name, share, best),
+                None => println!("{} wins {}", name, share),
+            }
+        }
+    }
+
     Ok(())
 
 }
\ No newline at end of file
@@ -0,0 +1,242 @@
+use std::ops::{BitAnd, BitOr, Sub};
+
+use crate::{Card, Hand, HandError, Rank, Suit};
+
+// u64 backed set of cards for the hot simulation paths
+// each suit gets a 16 bit lane, bit (suit * 16 + rank) is set when the card is in the set
+// ranks run from the deuce in bit 0 up to the ace in bit 12
+
+const RANK_LANE: u64 = 0x1fff;
+const FULL_DECK: u64 = RANK_LANE | RANK_LANE << 16 | RANK_LANE << 32 | RANK_LANE << 48;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CardSet(u64);
+
+impl CardSet {
+    pub const EMPTY: CardSet = CardSet(0);
+
+    pub fn new() -> Self {
+        Self::EMPTY
+    }
+
+    /// all 52 cards
+    pub fn full() -> Self {
+        CardSet(FULL_DECK)
+    }
+
+    pub fn from_bits(bits: u64) -> Self {
+        CardSet(bits & FULL_DECK)
+    }
+
+    pub fn bits(&self) -> u64 {
+        self.0
+    }
+
+    fn bit(card: &Card) -> u64 {
+        1u64 << (card.suit.index() * 16 + card.rank.high_value() - 2)
+    }
+
+    pub fn add(&mut self, card: Card) {
+        self.0 |= Self::bit(&card);
+    }
+
+    pub fn remove(&mut self, card: Card) {
+        self.0 &= !Self::bit(&card);
+    }
+
+    pub fn contains(&self, card: Card) -> bool {
+        self.0 & Self::bit(&card) != 0
+    }
+
+    pub fn union(self, other: CardSet) -> CardSet {
+        CardSet(self.0 | other.0)
+    }
+
+    pub fn intersection(self, other: CardSet) -> CardSet {
+        CardSet(self.0 & other.0)
+    }
+
+    pub fn difference(self, other: CardSet) -> CardSet {
+        CardSet(self.0 & !other.0)
+    }
+
+    pub fn is_disjoint(&self, other: &CardSet) -> bool {
+        self.0 & other.0 == 0
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// 13 bit rank mask of a single suit
+    pub fn suit_mask(&self, suit: &Suit) -> u16 {
+        ((self.0 >> (suit.index() * 16)) & RANK_LANE) as u16
+    }
+
+    /// 13 bit mask of every rank present in any suit
+    pub fn rank_mask(&self) -> u16 {
+        ((self.0 | self.0 >> 16 | self.0 >> 32 | self.0 >> 48) & RANK_LANE) as u16
+    }
+
+    pub fn iter(&self) -> CardSetIter {
+        CardSetIter(self.0)
+    }
+
+    pub fn to_hand(&self) -> Result<Hand, HandError> {
+        Hand::new(self.iter().collect())
+    }
+}
+
+/// yields cards from the lowest bit up, deuce of clubs first
+#[derive(Debug, Clone)]
+pub struct CardSetIter(u64);
+
+impl Iterator for CardSetIter {
+    type Item = Card;
+
+    fn next(&mut self) -> Option<Card> {
+        if self.0 == 0 {
+            return None;
+        }
+        let idx = self.0.trailing_zeros() as usize;
+        self.0 &= self.0 - 1;
+        Some(Card::new(&Rank::ALL[idx % 16], &Suit::ALL[idx / 16]))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = self.0.count_ones() as usize;
+        (n, Some(n))
+    }
+}
+
+impl ExactSizeIterator for CardSetIter {}
+
+impl IntoIterator for CardSet {
+    type Item = Card;
+    type IntoIter = CardSetIter;
+
+    fn into_iter(self) -> CardSetIter {
+        self.iter()
+    }
+}
+
+impl FromIterator<Card> for CardSet {
+    fn from_iter<I: IntoIterator<Item = Card>>(iter: I) -> Self {
+        let mut set = CardSet::new();
+        for c in iter {
+            set.add(c);
+        }
+        set
+    }
+}
+
+impl From<Card> for CardSet {
+    fn from(card: Card) -> Self {
+        CardSet(Self::bit(&card))
+    }
+}
+
+impl From<&[Card]> for CardSet {
+    fn from(cards: &[Card]) -> Self {
+        cards.iter().copied().collect()
+    }
+}
+
+impl From<&Hand> for CardSet {
+    fn from(hand: &Hand) -> Self {
+        hand.cards.iter().copied().collect()
+    }
+}
+
+impl BitOr for CardSet {
+    type Output = CardSet;
+
+    fn bitor(self, rhs: CardSet) -> CardSet {
+        self.union(rhs)
+    }
+}
+
+impl BitAnd for CardSet {
+    type Output = CardSet;
+
+    fn bitand(self, rhs: CardSet) -> CardSet {
+        self.intersection(rhs)
+    }
+}
+
+impl Sub for CardSet {
+    type Output = CardSet;
+
+    fn sub(self, rhs: CardSet) -> CardSet {
+        self.difference(rhs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn card(s: &str) -> Card {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn add_remove_contains() {
+        let mut set = CardSet::new();
+        set.add(card("Ah"));
+        set.add(card("Ah"));
+        set.add(card("2c"));
+        assert!(set.contains(card("Ah")) && set.contains(card("2c")));
+        assert!(!set.contains(card("As")));
+        assert_eq!(set.len(), 2);
+        set.remove(card("Ah"));
+        set.remove(card("Kd"));
+        assert_eq!(set, CardSet::from(card("2c")));
+    }
+
+    #[test]
+    fn set_operations() {
+        let a: CardSet = ["Ah", "Kh", "Qh"].iter().map(|s| card(s)).collect();
+        let b: CardSet = ["Qh", "Jh"].iter().map(|s| card(s)).collect();
+        assert_eq!((a | b).len(), 4);
+        assert_eq!((a & b).len(), 1);
+        assert_eq!((a - b).len(), 2);
+        assert_eq!(a.union(b), a | b);
+        assert!(!a.is_disjoint(&b));
+        assert!((a - b).is_disjoint(&b));
+        assert_eq!(CardSet::full().len(), 52);
+        assert_eq!(CardSet::full().difference(a).len(), 49);
+    }
+
+    #[test]
+    fn from_bits_keeps_only_real_cards() {
+        assert_eq!(CardSet::from_bits(u64::MAX), CardSet::full());
+        // bits 13 to 15 of every lane have no card
+        assert!(CardSet::from_bits(0xe000_e000_e000_e000).is_empty());
+    }
+
+    #[test]
+    fn iter_order_and_index_round_trip() {
+        let order: Vec<String> = CardSet::full().iter().take(14).map(|c| c.as_string()).collect();
+        assert_eq!(&order[..2], ["2c", "3c"]);
+        assert_eq!(&order[12..], ["Ac", "2d"]);
+        let mut indices: Vec<u8> = CardSet::full().iter().map(|c| c.index()).collect();
+        for c in CardSet::full() {
+            assert_eq!(Card::from_index(c.index()), Some(c));
+        }
+        indices.sort_unstable();
+        assert_eq!(indices, (0..52).collect::<Vec<u8>>());
+        assert_eq!(Card::from_index(52), None);
+    }
+
+    #[test]
+    fn masks() {
+        let set: CardSet = ["Ah", "Kh", "As", "2c"].iter().map(|s| card(s)).collect();
+        assert_eq!(set.suit_mask(&Suit::Hearts), 1 << 12 | 1 << 11);
+        assert_eq!(set.rank_mask(), 1 << 12 | 1 << 11 | 1);
+    }
+}
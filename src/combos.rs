@@ -0,0 +1,126 @@
+use crate::cardset::CardSet;
+use crate::{Card, Deck, Variant};
+
+// exhaustive enumeration over the deck for exact answers on turn and river spots
+// every iterator yields CardSets so nothing is allocated per combination
+
+/// every k-card subset of a list of cards, in lexicographic order of their positions
+#[derive(Debug, Clone)]
+pub struct Combinations {
+    cards: Vec<Card>,
+    indices: Vec<usize>,
+    done: bool,
+}
+
+impl Combinations {
+    pub fn new(cards: Vec<Card>, k: usize) -> Self {
+        let done = k > cards.len();
+        Self {
+            cards,
+            indices: (0..k).collect(),
+            done,
+        }
+    }
+}
+
+impl Iterator for Combinations {
+    type Item = CardSet;
+
+    fn next(&mut self) -> Option<CardSet> {
+        if self.done {
+            return None;
+        }
+        let set: CardSet = self.indices.iter().map(|i| self.cards[*i]).collect();
+
+        // move the rightmost index that still has room and pack the rest behind it
+        let (n, k) = (self.cards.len(), self.indices.len());
+        match (0..k).rev().find(|i| self.indices[*i] < n - k + i) {
+            Some(i) => {
+                self.indices[i] += 1;
+                for j in i + 1..k {
+                    self.indices[j] = self.indices[j - 1] + 1;
+                }
+            }
+            None => self.done = true,
+        }
+        Some(set)
+    }
+}
+
+impl Deck {
+    // every k-card subset of the cards left in the deck
+    pub fn combinations(&self, k: usize) -> Combinations {
+        Combinations::new(self.cards.clone(), k)
+    }
+}
+
+// cards of a full deck that are not dead
+fn live_cards(dead: &[Card], variant: Variant) -> Vec<Card> {
+    Deck::for_variant(variant).cards.into_iter().filter(|c| !dead.contains(c)).collect()
+}
+
+/// every 2-card starting hand that avoids the dead cards, 1326 of them with none dead
+pub fn hole_combos(dead: &[Card]) -> Combinations {
+    hole_combos_with(dead, Variant::Holdem)
+}
+
+pub fn hole_combos_with(dead: &[Card], variant: Variant) -> Combinations {
+    Combinations::new(live_cards(dead, variant), 2)
+}
+
+/// every full five card board that extends a partial board (0 to 5 cards)
+/// the board cards and any dead cards, such as known hole cards, are never dealt again
+pub fn board_completions(board: &[Card], dead: &[Card]) -> impl Iterator<Item = CardSet> {
+    board_completions_with(board, dead, Variant::Holdem)
+}
+
+pub fn board_completions_with(board: &[Card], dead: &[Card], variant: Variant) -> impl Iterator<Item = CardSet> {
+    let board_set = CardSet::from(board);
+    let unseen: Vec<Card> = live_cards(dead, variant)
+        .into_iter()
+        .filter(|c| !board_set.contains(*c))
+        .collect();
+    Combinations::new(unseen, 5usize.saturating_sub(board.len())).map(move |runout| runout | board_set)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Hand;
+
+    fn cards(s: &str) -> Vec<Card> {
+        s.parse::<Hand>().unwrap().cards
+    }
+
+    #[test]
+    fn hole_combos_skip_dead_cards() {
+        assert_eq!(hole_combos(&[]).count(), 1326);
+        let dead = cards("AhKd");
+        let ace: Card = "Ah".parse().unwrap();
+        assert_eq!(hole_combos(&dead).count(), 50 * 49 / 2);
+        assert!(hole_combos(&dead).all(|c| !c.contains(ace)));
+        assert_eq!(hole_combos_with(&[], Variant::ShortDeck).count(), 36 * 35 / 2);
+    }
+
+    #[test]
+    fn board_completions_fill_the_board() {
+        let flop = cards("2c7d9s");
+        let dead = cards("AhKhQsQd");
+        let boards: Vec<CardSet> = board_completions(&flop, &dead).collect();
+        assert_eq!(boards.len(), 45 * 44 / 2);
+        let flop_set = CardSet::from(flop.as_slice());
+        let dead_set = CardSet::from(dead.as_slice());
+        assert!(boards.iter().all(|b| b.len() == 5 && flop_set.difference(*b).is_empty() && b.is_disjoint(&dead_set)));
+        // a full board only completes to itself
+        assert_eq!(board_completions(&cards("2c7d9sTc3h"), &[]).count(), 1);
+    }
+
+    #[test]
+    fn edge_sizes() {
+        let few = cards("2c3c4c");
+        let empty: Vec<CardSet> = Combinations::new(few.clone(), 0).collect();
+        assert_eq!(empty, vec![CardSet::EMPTY]);
+        assert_eq!(Combinations::new(few.clone(), 3).count(), 1);
+        assert_eq!(Combinations::new(few, 4).count(), 0);
+    }
+}
@@ -0,0 +1,500 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::cardset::CardSet;
+use crate::combos::board_completions_with;
+use crate::eval::{evaluate_set_with, HandRank};
+use crate::range::Range;
+use crate::{Card, Deck, GameMaster, Variant};
+
+// hand vs range and range vs range equity for 2 to 6 players, and known hands at a full table
+// small spots are enumerated exactly, every deal of combos against every runout,
+// bigger ones fall back to sampling a deal and a runout per trial
+
+pub const MAX_PLAYERS: usize = 6;
+// showdowns we're willing to enumerate before switching to monte carlo
+pub const EXACT_LIMIT: u64 = 1_000_000;
+pub const MONTE_CARLO_TRIALS: u64 = 100_000;
+// attempts at drawing non overlapping combos before a trial gives up
+const MAX_REDRAWS: usize = 1_000;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EquityError {
+    PlayerCount(usize),
+    HoleCards(usize),
+    BoardSize(usize),
+    // a player's range has nothing left once the board and dead cards are removed
+    EmptyRange(usize),
+    NoValidDeal,
+}
+
+impl fmt::Display for EquityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EquityError::PlayerCount(n) => write!(f, "can't work out equity for {} players", n),
+            EquityError::HoleCards(n) => write!(f, "a hand needs 2 distinct hole cards, got {}", n),
+            EquityError::BoardSize(n) => write!(f, "a board has at most 5 cards, got {}", n),
+            EquityError::EmptyRange(i) => write!(f, "range of player {} is empty after card removal", i),
+            EquityError::NoValidDeal => write!(f, "the ranges can't be dealt without sharing a card"),
+        }
+    }
+}
+
+impl Error for EquityError {}
+
+/// equity of one combo within its player's range
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComboEquity {
+    pub combo: CardSet,
+    pub equity: f64,
+    // share of the player's showdowns this combo took part in
+    pub frequency: f64,
+}
+
+/// win, tie and loss are fractions of all showdowns and add up to 1
+/// equity counts a tie as the player's share of the pot
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayerEquity {
+    pub win: f64,
+    pub tie: f64,
+    pub loss: f64,
+    pub equity: f64,
+    pub combos: Vec<ComboEquity>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EquityResult {
+    pub players: Vec<PlayerEquity>,
+    pub exact: bool,
+    // showdowns evaluated, runouts times deals when exact
+    pub samples: u64,
+}
+
+// weighted sums of every showdown result, normalised once at the end
+struct Tally {
+    win: Vec<f64>,
+    tie: Vec<f64>,
+    equity: Vec<f64>,
+    combos: Vec<BTreeMap<CardSet, (f64, f64)>>,
+    // scratch space for one showdown
+    ranks: Vec<Option<HandRank>>,
+    total: f64,
+    samples: u64,
+}
+
+impl Tally {
+    fn new(players: usize) -> Self {
+        Self {
+            win: vec![0.0; players],
+            tie: vec![0.0; players],
+            equity: vec![0.0; players],
+            combos: vec![BTreeMap::new(); players],
+            ranks: vec![None; players],
+            total: 0.0,
+            samples: 0,
+        }
+    }
+
+    fn record(&mut self, hands: &[CardSet], board: CardSet, weight: f64, variant: Variant) {
+        for (i, hole) in hands.iter().enumerate() {
+            self.ranks[i] = evaluate_set_with(*hole | board, variant).ok();
+        }
+        let best = self.ranks.iter().flatten().max().copied();
+        let winners = self.ranks.iter().filter(|r| r.is_some() && **r == best).count();
+
+        for (i, hole) in hands.iter().enumerate() {
+            let share = if self.ranks[i].is_some() && self.ranks[i] == best { 1.0 / winners as f64 } else { 0.0 };
+            if share == 1.0 {
+                self.win[i] += weight;
+            } else if share > 0.0 {
+                self.tie[i] += weight;
+            }
+            self.equity[i] += weight * share;
+            let entry = self.combos[i].entry(*hole).or_insert((0.0, 0.0));
+            entry.0 += weight * share;
+            entry.1 += weight;
+        }
+        self.total += weight;
+        self.samples += 1;
+    }
+
+    fn finish(self, exact: bool) -> Result<EquityResult, EquityError> {
+        if self.total == 0.0 {
+            return Err(EquityError::NoValidDeal);
+        }
+        let total = self.total;
+        let players = (0..self.win.len())
+            .map(|i| PlayerEquity {
+                win: self.win[i] / total,
+                tie: self.tie[i] / total,
+                loss: (total - self.win[i] - self.tie[i]).max(0.0) / total,
+                equity: self.equity[i] / total,
+                combos: self.combos[i]
+                    .iter()
+                    .map(|(combo, (won, seen))| ComboEquity {
+                        combo: *combo,
+                        equity: won / seen,
+                        frequency: seen / total,
+                    })
+                    .collect(),
+            })
+            .collect();
+        Ok(EquityResult { players, exact, samples: self.samples })
+    }
+}
+
+/// one known hand against a range
+pub fn hand_vs_range(hero: &[Card], villain: &Range, board: &[Card]) -> Result<EquityResult, EquityError> {
+    let hero_set = CardSet::from(hero);
+    if hero.len() != 2 || hero_set.len() != 2 {
+        return Err(EquityError::HoleCards(hero.len()));
+    }
+    range_equity(&[Range::from(hero_set), villain.clone()], board, &[])
+}
+
+pub fn range_equity(ranges: &[Range], board: &[Card], dead: &[Card]) -> Result<EquityResult, EquityError> {
+    range_equity_with(ranges, board, dead, Variant::Holdem, &mut StdRng::from_entropy())
+}
+
+/// equity of every player's range on a partial board (0 to 5 cards)
+/// dead cards, such as folded hands, are never dealt to anyone
+/// the rng is only drawn from when the spot is too big to enumerate
+pub fn range_equity_with<R: Rng + ?Sized>(
+    ranges: &[Range],
+    board: &[Card],
+    dead: &[Card],
+    variant: Variant,
+    rng: &mut R,
+) -> Result<EquityResult, EquityError> {
+    if ranges.len() < 2 || ranges.len() > MAX_PLAYERS {
+        return Err(EquityError::PlayerCount(ranges.len()));
+    }
+    equity_of(ranges, board, dead, variant, rng)
+}
+
+/// equity of known hole cards for any number of players, as at a real table
+pub fn hand_equity_with<R: Rng + ?Sized>(
+    hands: &[CardSet],
+    board: &[Card],
+    dead: &[Card],
+    variant: Variant,
+    rng: &mut R,
+) -> Result<EquityResult, EquityError> {
+    if hands.len() < 2 || 2 * hands.len() + 5 > variant.deck_size() {
+        return Err(EquityError::PlayerCount(hands.len()));
+    }
+    let ranges: Vec<Range> = hands.iter().map(|h| Range::from(*h)).collect();
+    equity_of(&ranges, board, dead, variant, rng)
+}
+
+fn equity_of<R: Rng + ?Sized>(
+    ranges: &[Range],
+    board: &[Card],
+    dead: &[Card],
+    variant: Variant,
+    rng: &mut R,
+) -> Result<EquityResult, EquityError> {
+    if board.len() > 5 {
+        return Err(EquityError::BoardSize(board.len()));
+    }
+    let board_set = CardSet::from(board);
+    let blocked = board_set | CardSet::from(dead);
+    let mut live: Vec<Vec<(CardSet, f64)>> = Vec::new();
+    for (i, range) in ranges.iter().enumerate() {
+        let combos: Vec<(CardSet, f64)> = range
+            .combos()
+            .filter(|(c, _)| c.is_disjoint(&blocked) && c.iter().all(|card| variant.uses_rank(card.rank)))
+            .collect();
+        if combos.is_empty() {
+            return Err(EquityError::EmptyRange(i));
+        }
+        live.push(combos);
+    }
+
+    let to_come = 5 - board.len();
+    let unseen = variant.deck_size().saturating_sub(blocked.len() + 2 * ranges.len());
+    let deals = live.iter().fold(1u64, |n, combos| n.saturating_mul(combos.len() as u64));
+    if deals.saturating_mul(binomial(unseen, to_come)) <= EXACT_LIMIT {
+        exact_equity(&live, board, dead, variant)
+    } else {
+        sampled_equity(&live, board_set, blocked, variant, rng)
+    }
+}
+
+fn exact_equity(live: &[Vec<(CardSet, f64)>], board: &[Card], dead: &[Card], variant: Variant) -> Result<EquityResult, EquityError> {
+    let mut tally = Tally::new(live.len());
+    let mut hands: Vec<CardSet> = Vec::with_capacity(live.len());
+    for_each_deal(live, CardSet::from(dead), &mut hands, 1.0, &mut |hands, used, weight| {
+        let used: Vec<Card> = used.iter().collect();
+        for runout in board_completions_with(board, &used, variant) {
+            tally.record(hands, runout, weight, variant);
+        }
+    });
+    tally.finish(true)
+}
+
+// calls back with every way of giving each player one combo without a shared card
+fn for_each_deal<F: FnMut(&[CardSet], CardSet, f64)>(
+    live: &[Vec<(CardSet, f64)>],
+    used: CardSet,
+    hands: &mut Vec<CardSet>,
+    weight: f64,
+    f: &mut F,
+) {
+    let player = hands.len();
+    if player == live.len() {
+        f(hands, used, weight);
+        return;
+    }
+    for (combo, w) in &live[player] {
+        if combo.is_disjoint(&used) {
+            hands.push(*combo);
+            for_each_deal(live, used | *combo, hands, weight * w, f);
+            hands.pop();
+        }
+    }
+}
+
+fn sampled_equity<R: Rng + ?Sized>(
+    live: &[Vec<(CardSet, f64)>],
+    board: CardSet,
+    blocked: CardSet,
+    variant: Variant,
+    rng: &mut R,
+) -> Result<EquityResult, EquityError> {
+    // running weight totals so a combo is drawn in proportion to its weight
+    let cumulative: Vec<Vec<f64>> = live
+        .iter()
+        .map(|combos| {
+            combos
+                .iter()
+                .scan(0.0, |sum, (_, w)| {
+                    *sum += w;
+                    Some(*sum)
+                })
+                .collect()
+        })
+        .collect();
+    let deck = Deck::for_variant(variant).cards;
+
+    let mut tally = Tally::new(live.len());
+    let mut hands: Vec<CardSet> = vec![CardSet::EMPTY; live.len()];
+    for _ in 0..MONTE_CARLO_TRIALS {
+        // redraw the whole deal on a collision, drawing players in turn would skew the weights
+        let mut used = blocked;
+        let mut dealt = false;
+        for _ in 0..MAX_REDRAWS {
+            used = blocked;
+            dealt = true;
+            for (i, sums) in cumulative.iter().enumerate() {
+                let target = rng.gen::<f64>() * sums[sums.len() - 1];
+                let pick = sums.partition_point(|s| *s <= target).min(sums.len() - 1);
+                let combo = live[i][pick].0;
+                if !combo.is_disjoint(&used) {
+                    dealt = false;
+                    break;
+                }
+                hands[i] = combo;
+                used = used | combo;
+            }
+            if dealt {
+                break;
+            }
+        }
+        if !dealt {
+            return Err(EquityError::NoValidDeal);
+        }
+
+        let mut unseen: Vec<Card> = deck.iter().copied().filter(|c| !used.contains(*c)).collect();
+        let mut runout = board;
+        for k in 0..5 - board.len() {
+            let j = rng.gen_range(k..unseen.len());
+            unseen.swap(k, j);
+            runout.add(unseen[k]);
+        }
+        tally.record(&hands, runout, 1.0, variant);
+    }
+    tally.finish(false)
+}
+
+impl GameMaster {
+    /// refreshes hand_equity for every seat against the current table cards
+    /// folded hands are dead cards and get no equity, a lone live player has all of it
+    pub fn update_equity(&mut self) -> Result<(), Box<dyn Error>> {
+        let board: Vec<Card> = self.gamestate.table_cards.clone().unwrap_or_default();
+        let mut live: Vec<usize> = Vec::new();
+        let mut dead: Vec<Card> = Vec::new();
+        for (seat, p) in self.players.iter().enumerate() {
+            match &p.hand {
+                Some(h) if p.folded => dead.extend(h.cards.iter().copied()),
+                Some(h) if h.cards.len() == 2 => live.push(seat),
+                _ => {}
+            }
+        }
+
+        for p in &mut self.players {
+            p.hand_equity = 0.0;
+        }
+        match live.len() {
+            0 => {}
+            1 => self.players[live[0]].hand_equity = 1.0,
+            _ => {
+                let hands: Vec<CardSet> = live
+                    .iter()
+                    .filter_map(|seat| self.players[*seat].hand.as_ref())
+                    .map(CardSet::from)
+                    .collect();
+                // a seeded game samples the same way every time, one stream per street
+                let mut rng = match self.gamestate.seed {
+                    Some(s) => StdRng::seed_from_u64(s.wrapping_add(board.len() as u64)),
+                    None => StdRng::from_entropy(),
+                };
+                let table = if board.is_empty() {
+                    "the deal".to_string()
+                } else {
+                    board.iter().map(|c| c.as_string()).collect::<Vec<_>>().join(" ")
+                };
+                let result = hand_equity_with(&hands, &board, &dead, self.gamestate.variant, &mut rng)?;
+                for (seat, equity) in live.iter().zip(&result.players) {
+                    let p = &mut self.players[*seat];
+                    p.hand_equity = equity.equity;
+                    if let Some(h) = &p.hand {
+                        println!("{} has {:.1}% equity on {}.", h, 100.0 * equity.equity, table);
+                    }
+                }
+            }
+        }
+        self.gamestate.players = Some(self.players.clone());
+        Ok(())
+    }
+}
+
+fn binomial(n: usize, k: usize) -> u64 {
+    if k > n {
+        return 0;
+    }
+    (0..k).fold(1u64, |acc, i| acc * (n - i) as u64 / (i + 1) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Hand;
+
+    fn cards(s: &str) -> Vec<Card> {
+        s.parse::<Hand>().unwrap().cards
+    }
+
+    fn range(s: &str) -> Range {
+        s.parse().unwrap()
+    }
+
+    fn seeded() -> StdRng {
+        StdRng::seed_from_u64(7)
+    }
+
+    #[test]
+    fn exact_turn_equity_counts_the_outs() {
+        // only the three aces and three kings left in 44 rivers beat the queens
+        let hands = [CardSet::from(cards("AhKh").as_slice()), CardSet::from(cards("QsQd").as_slice())];
+        let result = hand_equity_with(&hands, &cards("2c7d9sTc"), &[], Variant::Holdem, &mut seeded()).unwrap();
+        assert!(result.exact);
+        assert_eq!(result.samples, 44);
+        assert!((result.players[0].equity - 6.0 / 44.0).abs() < 1e-12);
+        assert!((result.players[1].win - 38.0 / 44.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn hand_vs_range_adds_up() {
+        let result = hand_vs_range(&cards("AhKh"), &range("QQ+, AKo"), &cards("2c7d9s")).unwrap();
+        assert!(result.exact);
+        let hero = &result.players[0];
+        assert!((hero.win + hero.tie + hero.loss - 1.0).abs() < 1e-9);
+        assert!(hero.tie > 0.0);
+        // AA and KK lose the combos with the hero's cards, AKo keeps the six without a heart
+        let villain = &result.players[1];
+        assert_eq!(villain.combos.len(), 3 + 3 + 6 + 6);
+        let frequency: f64 = villain.combos.iter().map(|c| c.frequency).sum();
+        assert!((frequency - 1.0).abs() < 1e-9);
+        let equity: f64 = villain.combos.iter().map(|c| c.equity * c.frequency).sum();
+        assert!((equity - villain.equity).abs() < 1e-9);
+        assert!((hero.equity + villain.equity - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(hand_vs_range(&cards("AhKhQh"), &range("QQ"), &[]), Err(EquityError::HoleCards(3)));
+        let ace: Card = "Ah".parse().unwrap();
+        assert_eq!(hand_vs_range(&[ace, ace], &range("QQ"), &[]), Err(EquityError::HoleCards(2)));
+
+        let seven: Vec<Range> = (0..7).map(|_| Range::full()).collect();
+        assert_eq!(range_equity(&seven, &[], &[]).unwrap_err(), EquityError::PlayerCount(7));
+        assert_eq!(hand_vs_range(&cards("AhKh"), &range("AsAd"), &cards("As7d9s")), Err(EquityError::EmptyRange(1)));
+        // nothing is blocked by the board, the two hands just can't both be dealt
+        assert_eq!(hand_vs_range(&cards("AhKh"), &range("AhAs"), &[]), Err(EquityError::NoValidDeal));
+        assert_eq!(
+            range_equity(&[range("AhAs"), range("AhAd")], &cards("2c7d9sTc"), &[]),
+            Err(EquityError::NoValidDeal)
+        );
+        assert_eq!(range_equity(&[range("AA"), range("KK")], &cards("2c7d9sTc3h4h"), &[]).unwrap_err(), EquityError::BoardSize(6));
+    }
+
+    #[test]
+    fn sampling_agrees_with_enumeration() {
+        let board = cards("2c7d9s");
+        let blocked = CardSet::from(board.as_slice());
+        let live: Vec<Vec<(CardSet, f64)>> = [range("AhKh"), range("TT+, AQs+, 87s:0.5")]
+            .iter()
+            .map(|r| r.without_blocked(blocked).combos().collect())
+            .collect();
+        let exact = exact_equity(&live, &board, &[], Variant::Holdem).unwrap();
+        let sampled = sampled_equity(&live, blocked, blocked, Variant::Holdem, &mut seeded()).unwrap();
+        assert!(!sampled.exact);
+        assert_eq!(sampled.samples, MONTE_CARLO_TRIALS);
+        // a standard error of about 0.0015 at this many trials
+        for (e, s) in exact.players.iter().zip(&sampled.players) {
+            assert!((e.equity - s.equity).abs() < 0.01, "{} vs {}", e.equity, s.equity);
+            assert!((e.win - s.win).abs() < 0.01);
+        }
+    }
+
+    #[test]
+    fn folded_hands_are_dead_and_get_no_equity() {
+        use crate::{Game, Player};
+        let holdings = ["AhKh", "QsQd", "9h8h"];
+        let players: Vec<Player> = holdings
+            .iter()
+            .enumerate()
+            .map(|(i, h)| {
+                let mut p = Player::new(format!("p{}", i));
+                p.hand = Some(h.parse().unwrap());
+                p
+            })
+            .collect();
+        let mut gm = GameMaster::new(Game::new(Some(5)), players);
+        let board = cards("2h7h9s");
+        gm.gamestate.table_cards = Some(board.clone());
+        gm.players[2].fold();
+        gm.update_equity().unwrap();
+
+        assert_eq!(gm.players[2].hand_equity, 0.0);
+        // the folded hearts never come on the turn or river
+        let hands = [CardSet::from(cards("AhKh").as_slice()), CardSet::from(cards("QsQd").as_slice())];
+        let direct = hand_equity_with(&hands, &board, &cards("9h8h"), Variant::Holdem, &mut seeded()).unwrap();
+        assert_eq!(gm.players[0].hand_equity, direct.players[0].equity);
+        assert_eq!(gm.players[1].hand_equity, direct.players[1].equity);
+        let live_only = hand_equity_with(&hands, &board, &[], Variant::Holdem, &mut seeded()).unwrap();
+        assert_ne!(gm.players[0].hand_equity, live_only.players[0].equity);
+    }
+
+    #[test]
+    fn big_spots_switch_to_sampling() {
+        let result = hand_vs_range(&cards("AhKh"), &Range::full(), &[]).unwrap();
+        assert!(!result.exact);
+        assert_eq!(result.samples, MONTE_CARLO_TRIALS);
+    }
+}
@@ -0,0 +1,389 @@
+use std::cmp::Reverse;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::cardset::CardSet;
+use crate::combos::{board_completions_with, Combinations};
+use crate::{Card, HandError, Rank, Suit, Variant};
+
+// native hand evaluator, ranks any 5, 6 or 7 cards without going through aya_poker
+
+/// hand categories ordered from weakest to strongest
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum HandCategory {
+    HighCard,
+    OnePair,
+    TwoPair,
+    ThreeOfAKind,
+    Straight,
+    Flush,
+    FullHouse,
+    FourOfAKind,
+    StraightFlush,
+}
+
+impl HandCategory {
+    pub fn name(&self) -> &'static str {
+        match self {
+            HandCategory::HighCard => "High card",
+            HandCategory::OnePair => "Pair",
+            HandCategory::TwoPair => "Two pair",
+            HandCategory::ThreeOfAKind => "Three of a kind",
+            HandCategory::Straight => "Straight",
+            HandCategory::Flush => "Flush",
+            HandCategory::FullHouse => "Full house",
+            HandCategory::FourOfAKind => "Four of a kind",
+            HandCategory::StraightFlush => "Straight flush",
+        }
+    }
+
+    // position in the variant's ranking, short deck swaps flushes and full houses
+    pub(crate) fn strength(self, variant: Variant) -> u8 {
+        match (variant, self) {
+            (Variant::ShortDeck, HandCategory::Flush) => HandCategory::FullHouse as u8,
+            (Variant::ShortDeck, HandCategory::FullHouse) => HandCategory::Flush as u8,
+            _ => self as u8,
+        }
+    }
+}
+
+/// comparable strength of the best five cards in a hand
+/// category is compared first, then the kickers from most to least significant
+/// kickers hold rank values (2 = deuce .. 14 = ace), unused slots are 0
+/// only ranks from the same variant should be compared
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct HandRank {
+    strength: u8,
+    pub category: HandCategory,
+    pub kickers: [u8; 5],
+}
+
+impl HandRank {
+    fn new(variant: Variant, category: HandCategory, ranks: &[u8]) -> Self {
+        let mut kickers = [0u8; 5];
+        for (slot, r) in kickers.iter_mut().zip(ranks) {
+            *slot = *r;
+        }
+        Self { strength: category.strength(variant), category, kickers }
+    }
+
+    /// readable result such as "Full house, Kings full of Sevens"
+    pub fn description(&self) -> String {
+        let rank = |i: usize| Rank::from_value(self.kickers[i]).unwrap_or(&Rank::Two);
+        // flushes and high card hands differ all the way down, so every card is named
+        let all_five = || (0..5).map(|i| rank(i).name()).collect::<Vec<_>>().join("-");
+        match self.category {
+            HandCategory::HighCard => format!("High card, {}", all_five()),
+            HandCategory::OnePair => format!("Pair of {}", rank(0).plural_name()),
+            HandCategory::TwoPair => format!("Two pair, {} and {}", rank(0).plural_name(), rank(1).plural_name()),
+            HandCategory::ThreeOfAKind => format!("Three of a kind, {}", rank(0).plural_name()),
+            HandCategory::Straight => format!("Straight, {} high", rank(0).name()),
+            HandCategory::Flush => format!("Flush, {} high", all_five()),
+            HandCategory::FullHouse => format!("Full house, {} full of {}", rank(0).plural_name(), rank(1).plural_name()),
+            HandCategory::FourOfAKind => format!("Four of a kind, {}", rank(0).plural_name()),
+            HandCategory::StraightFlush if self.kickers[0] == 14 => "Royal flush".to_string(),
+            HandCategory::StraightFlush => format!("Straight flush, {} high", rank(0).name()),
+        }
+    }
+}
+
+impl fmt::Display for HandCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl fmt::Display for HandRank {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.description())
+    }
+}
+
+/// the five cards that play from a larger hand, ordered the way they read:
+/// grouped ranks first (trips before the pair in a full house), then kickers high to low
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BestHand {
+    pub rank: HandRank,
+    pub cards: Vec<Card>,
+}
+
+impl BestHand {
+    pub fn category(&self) -> HandCategory {
+        self.rank.category
+    }
+
+    pub fn description(&self) -> String {
+        self.rank.description()
+    }
+}
+
+impl fmt::Display for BestHand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let cards: Vec<String> = self.cards.iter().map(|c| c.as_string()).collect();
+        write!(f, "{} ({})", self.description(), cards.join(" "))
+    }
+}
+
+pub fn best_five(cards: &[Card]) -> Result<BestHand, HandError> {
+    best_five_with(cards, Variant::Holdem)
+}
+
+/// picks the strongest five card subset of 5 to 7 cards
+pub fn best_five_with(cards: &[Card], variant: Variant) -> Result<BestHand, HandError> {
+    // validates the size and rejects duplicate cards
+    let rank = evaluate_with(cards, variant)?;
+    let five = Combinations::new(cards.to_vec(), 5)
+        .find(|subset| evaluate_set_with(*subset, variant).ok() == Some(rank))
+        .ok_or(HandError::InvalidSize(cards.len()))?;
+
+    let mut ordered: Vec<Card> = five.iter().collect();
+    let group_size = |c: &Card| five.iter().filter(|o| o.rank == c.rank).count();
+    ordered.sort_by_key(|c| Reverse((group_size(c), *c.rank, *c.suit)));
+    // wheels read 5-4-3-2-A (9-8-7-6-A in short deck), so the ace goes to the back
+    let straight = matches!(rank.category, HandCategory::Straight | HandCategory::StraightFlush);
+    if straight && rank.kickers[0] != 14 && *ordered[0].rank == Rank::Ace {
+        ordered.rotate_left(1);
+    }
+    Ok(BestHand { rank, cards: ordered })
+}
+
+pub fn evaluate(cards: &[Card]) -> Result<HandRank, HandError> {
+    evaluate_with(cards, Variant::Holdem)
+}
+
+pub fn evaluate_with(cards: &[Card], variant: Variant) -> Result<HandRank, HandError> {
+    let set = CardSet::from(cards);
+    if set.len() != cards.len() {
+        let repeated = cards.iter().enumerate().find(|(i, c)| cards[..*i].contains(c));
+        if let Some((_, c)) = repeated {
+            return Err(HandError::DuplicateCard(*c));
+        }
+    }
+    evaluate_set_with(set, variant)
+}
+
+/// same as evaluate but straight from the bitmask, nothing is allocated per card
+pub fn evaluate_set(set: CardSet) -> Result<HandRank, HandError> {
+    evaluate_set_with(set, Variant::Holdem)
+}
+
+pub fn evaluate_set_with(set: CardSet, variant: Variant) -> Result<HandRank, HandError> {
+    if set.len() < 5 || set.len() > 7 {
+        return Err(HandError::InvalidSize(set.len()));
+    }
+    // every holdem card is fine, other variants play with fewer ranks
+    if variant != Variant::Holdem {
+        if let Some(c) = set.iter().find(|c| !variant.uses_rank(c.rank)) {
+            return Err(HandError::CardNotInVariant(c));
+        }
+    }
+    // bit i of a mask is set when rank value i+2 is present
+    let suit_masks = [
+        set.suit_mask(&Suit::Clubs),
+        set.suit_mask(&Suit::Diamonds),
+        set.suit_mask(&Suit::Hearts),
+        set.suit_mask(&Suit::Spades),
+    ];
+    let rank_mask = set.rank_mask();
+    let mut counts = [0u8; 13];
+    for (r, ct) in counts.iter_mut().enumerate() {
+        *ct = suit_masks.iter().filter(|m| *m & (1 << r) != 0).count() as u8;
+    }
+
+    // with at most 7 cards a flush rules out quads and full houses
+    if let Some(mask) = suit_masks.iter().find(|m| m.count_ones() >= 5) {
+        if let Some(high) = straight_high(*mask, variant) {
+            return Ok(HandRank::new(variant, HandCategory::StraightFlush, &[high]));
+        }
+        return Ok(HandRank::new(variant, HandCategory::Flush, &top_ranks(*mask, 5)));
+    }
+
+    // grouped ranks from highest to lowest, at most 2 trips or 3 pairs fit in 7 cards
+    let mut quads = 0u8;
+    let (mut trips, mut n_trips) = ([0u8; 2], 0);
+    let (mut pairs, mut n_pairs) = ([0u8; 3], 0);
+    for (r, ct) in counts.iter().enumerate().rev() {
+        let value = r as u8 + 2;
+        match ct {
+            4 => quads = value,
+            3 => {
+                trips[n_trips] = value;
+                n_trips += 1;
+            }
+            2 => {
+                pairs[n_pairs] = value;
+                n_pairs += 1;
+            }
+            _ => {}
+        }
+    }
+    // highest ranks left over once the grouped ranks are taken out
+    let kickers = |used: &[u8], n: usize| -> [u8; 5] {
+        let mut mask = rank_mask;
+        for r in used {
+            mask &= !(1 << (r - 2));
+        }
+        top_ranks(mask, n)
+    };
+
+    if quads > 0 {
+        let k = kickers(&[quads], 1);
+        return Ok(HandRank::new(variant, HandCategory::FourOfAKind, &[quads, k[0]]));
+    }
+    if n_trips > 0 && (n_trips > 1 || n_pairs > 0) {
+        // a second set of trips plays as the pair when it beats the best pair
+        let pair = if n_trips > 1 { trips[1].max(pairs[0]) } else { pairs[0] };
+        return Ok(HandRank::new(variant, HandCategory::FullHouse, &[trips[0], pair]));
+    }
+    if let Some(high) = straight_high(rank_mask, variant) {
+        return Ok(HandRank::new(variant, HandCategory::Straight, &[high]));
+    }
+    if n_trips > 0 {
+        let k = kickers(&[trips[0]], 2);
+        return Ok(HandRank::new(variant, HandCategory::ThreeOfAKind, &[trips[0], k[0], k[1]]));
+    }
+    if n_pairs > 1 {
+        let k = kickers(&pairs[..2], 1);
+        return Ok(HandRank::new(variant, HandCategory::TwoPair, &[pairs[0], pairs[1], k[0]]));
+    }
+    if n_pairs > 0 {
+        let k = kickers(&pairs[..1], 3);
+        return Ok(HandRank::new(variant, HandCategory::OnePair, &[pairs[0], k[0], k[1], k[2]]));
+    }
+    Ok(HandRank::new(variant, HandCategory::HighCard, &top_ranks(rank_mask, 5)))
+}
+
+/// share of the pot the first hand wins against the second, ties count as half
+/// every remaining board completion is enumerated so the result is exact
+pub fn equity_calculator(hero: &[Card], villain: &[Card], board: &[Card]) -> f64 {
+    equity_calculator_with(hero, villain, board, Variant::Holdem)
+}
+
+pub fn equity_calculator_with(hero: &[Card], villain: &[Card], board: &[Card], variant: Variant) -> f64 {
+    let (hero_set, villain_set) = (CardSet::from(hero), CardSet::from(villain));
+    let dead: Vec<Card> = hero.iter().chain(villain).copied().collect();
+
+    let mut score = 0.0;
+    let mut total = 0u64;
+    for runout in board_completions_with(board, &dead, variant) {
+        if let (Ok(h), Ok(v)) = (
+            evaluate_set_with(hero_set | runout, variant),
+            evaluate_set_with(villain_set | runout, variant),
+        ) {
+            if h > v {
+                score += 1.0;
+            } else if h == v {
+                score += 0.5;
+            }
+            total += 1;
+        }
+    }
+    if total == 0 { 0.0 } else { score / total as f64 }
+}
+
+// highest card of the best straight in a rank mask, the wheel counts as five high
+// short deck has no 2-5 so its wheel is A-6-7-8-9, nine high
+fn straight_high(mask: u16, variant: Variant) -> Option<u8> {
+    for low in (0..=8).rev() {
+        let window = 0b11111 << low;
+        if mask & window == window {
+            return Some(low as u8 + 6);
+        }
+    }
+    let (wheel, high) = match variant {
+        Variant::Holdem => (0b1_0000_0000_1111, 5),
+        Variant::ShortDeck => (0b1_0000_1111_0000, 9),
+    };
+    if mask & wheel == wheel {
+        return Some(high);
+    }
+    None
+}
+
+// the n highest rank values set in a mask, unused slots stay 0
+fn top_ranks(mask: u16, n: usize) -> [u8; 5] {
+    let mut ranks = [0u8; 5];
+    let mut found = 0;
+    for i in (0..13u8).rev() {
+        if found == n {
+            break;
+        }
+        if mask & (1 << i) != 0 {
+            ranks[found] = i + 2;
+            found += 1;
+        }
+    }
+    ranks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Hand;
+
+    fn rank(s: &str, variant: Variant) -> HandRank {
+        evaluate_with(&s.parse::<Hand>().unwrap().cards, variant).unwrap()
+    }
+
+    #[test]
+    fn wheel_is_a_five_high_straight() {
+        let wheel = rank("Ah2c3d4s5h9c", Variant::Holdem);
+        assert_eq!(wheel.category, HandCategory::Straight);
+        assert_eq!(wheel.kickers[0], 5);
+        assert!(wheel < rank("2c3d4s5h6h", Variant::Holdem));
+    }
+
+    #[test]
+    fn short_deck_wheel_runs_ace_to_nine() {
+        let low = rank("Ah6c7d8s9h", Variant::ShortDeck);
+        assert_eq!(low.category, HandCategory::Straight);
+        assert_eq!(low.kickers[0], 9);
+        assert!(low < rank("6c7d8s9hTh", Variant::ShortDeck));
+        // with the deuce through five gone it is only ace high in holdem
+        assert_eq!(rank("Ah6c7d8s9h", Variant::Holdem).category, HandCategory::HighCard);
+    }
+
+    #[test]
+    fn short_deck_rejects_cards_below_six() {
+        let cards = "2c3d4h5s6c".parse::<Hand>().unwrap().cards;
+        assert!(matches!(
+            evaluate_with(&cards, Variant::ShortDeck),
+            Err(HandError::CardNotInVariant(c)) if c.rank.high_value() < 6
+        ));
+        assert!(matches!(best_five_with(&cards, Variant::ShortDeck), Err(HandError::CardNotInVariant(_))));
+    }
+
+    #[test]
+    fn flush_and_full_house_swap_in_short_deck() {
+        let flush = "Ah9h7h6hThKc";
+        let full_house = "KsKdKcQhQd";
+        assert!(rank(flush, Variant::Holdem) < rank(full_house, Variant::Holdem));
+        assert!(rank(flush, Variant::ShortDeck) > rank(full_house, Variant::ShortDeck));
+    }
+
+    #[test]
+    fn two_trips_make_a_full_house() {
+        let hand = rank("9c9d9hKsKdKh2c", Variant::Holdem);
+        assert_eq!(hand.category, HandCategory::FullHouse);
+        assert_eq!(&hand.kickers[..2], &[13, 9]);
+        assert_eq!(hand.description(), "Full house, Kings full of Nines");
+    }
+
+    #[test]
+    fn flush_and_high_card_descriptions_name_the_kickers() {
+        assert_eq!(rank("AhJh9h7h5h2c", Variant::Holdem).description(), "Flush, Ace-Jack-Nine-Seven-Five high");
+        assert_eq!(rank("AhKc9d7s4h", Variant::Holdem).description(), "High card, Ace-King-Nine-Seven-Four");
+    }
+
+    #[test]
+    fn rejects_duplicates_and_bad_sizes() {
+        let ace: Card = "As".parse().unwrap();
+        let mut cards = "KsQsJsTs".parse::<Hand>().unwrap().cards;
+        assert!(matches!(evaluate(&cards), Err(HandError::InvalidSize(4))));
+        cards.push(ace);
+        cards.push(ace);
+        assert!(matches!(evaluate(&cards), Err(HandError::DuplicateCard(c)) if c == ace));
+        assert!(matches!(best_five(&cards), Err(HandError::DuplicateCard(_))));
+    }
+}
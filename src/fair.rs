@@ -0,0 +1,198 @@
+use std::error::Error;
+use std::fmt;
+
+use rand::rngs::OsRng;
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+
+use crate::{Card, Deck, Variant};
+
+// provably fair shuffling with commit/reveal
+//
+// 1. the server picks a secret server seed and publishes sha256(server seed) as the commitment,
+//    before any client seed is known, so it can't go looking for a seed that suits the clients
+// 2. every player sends a client seed
+// 3. the deck is shuffled from sha256(server seed + client seeds) with a fisher-yates pass
+//    whose random numbers come from sha256 in counter mode, so any sha256 implementation
+//    can replay it without depending on this crate's rng
+//    the pass starts from Deck::reset order: clubs, diamonds, hearts, spades, each suit running
+//    ace, two, three ... king (six to king on a short deck), and cards are dealt from the end
+// 4. after the hand the server seed is revealed and anyone can run verify() to check it against
+//    the commitment and rebuild the deck
+
+pub type ServerSeed = [u8; 32];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum VerifyError {
+    CommitmentMismatch { expected: String, rebuilt: String },
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifyError::CommitmentMismatch { expected, rebuilt } => write!(
+                f,
+                "revealed server seed hashes to {} but {} was published",
+                rebuilt, expected
+            ),
+        }
+    }
+}
+
+impl Error for VerifyError {}
+
+// fresh secret seed from the operating system rng
+pub fn new_server_seed() -> ServerSeed {
+    let mut seed = [0u8; 32];
+    OsRng.fill_bytes(&mut seed);
+    seed
+}
+
+/// hex encoded sha256 of the server seed, published before the client seeds are collected
+pub fn commitment(server_seed: &ServerSeed) -> String {
+    to_hex(&Sha256::digest(server_seed))
+}
+
+/// checks the revealed server seed against the published commitment and rebuilds the deck order
+pub fn verify(
+    server_seed: &ServerSeed,
+    client_seeds: &[String],
+    variant: Variant,
+    published: &str,
+) -> Result<Vec<Card>, VerifyError> {
+    let rebuilt = commitment(server_seed);
+    if !rebuilt.eq_ignore_ascii_case(published) {
+        return Err(VerifyError::CommitmentMismatch {
+            expected: published.to_string(),
+            rebuilt,
+        });
+    }
+    let mut deck = Deck::for_variant(variant);
+    deck.shuffle_fair(server_seed, client_seeds);
+    Ok(deck.cards)
+}
+
+impl Deck {
+    /// resets the deck and shuffles it from the combined seeds,
+    /// the commitment for the server seed has to be published before the client seeds are collected
+    pub fn shuffle_fair(&mut self, server_seed: &ServerSeed, client_seeds: &[String]) {
+        self.reset();
+        let mut stream = HashStream::new(server_seed, client_seeds);
+        for i in (1..self.cards.len()).rev() {
+            let j = stream.below(i as u64 + 1) as usize;
+            self.cards.swap(i, j);
+        }
+    }
+}
+
+// sha256 counter mode over the combined seed, read as little endian u64s
+struct HashStream {
+    seed: [u8; 32],
+    counter: u64,
+    block: [u8; 32],
+    offset: usize,
+}
+
+impl HashStream {
+    fn new(server_seed: &ServerSeed, client_seeds: &[String]) -> Self {
+        let mut hasher = Sha256::new();
+        hasher.update(server_seed);
+        // length prefixes keep ["ab", "c"] and ["a", "bc"] from combining to the same seed
+        for s in client_seeds {
+            hasher.update((s.len() as u64).to_le_bytes());
+            hasher.update(s.as_bytes());
+        }
+        Self {
+            seed: hasher.finalize().into(),
+            counter: 0,
+            block: [0u8; 32],
+            offset: 32,
+        }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        if self.offset == 32 {
+            let mut hasher = Sha256::new();
+            hasher.update(self.seed);
+            hasher.update(self.counter.to_le_bytes());
+            self.block = hasher.finalize().into();
+            self.counter += 1;
+            self.offset = 0;
+        }
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&self.block[self.offset..self.offset + 8]);
+        self.offset += 8;
+        u64::from_le_bytes(bytes)
+    }
+
+    // uniform in 0..bound, rejection sampling keeps it free of modulo bias
+    fn below(&mut self, bound: u64) -> u64 {
+        let zone = u64::MAX - (u64::MAX % bound);
+        loop {
+            let x = self.next_u64();
+            if x < zone {
+                return x % bound;
+            }
+        }
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn client_seeds() -> Vec<String> {
+        vec!["alice".to_string(), "bob".to_string()]
+    }
+
+    #[test]
+    fn verify_rebuilds_the_dealt_deck() {
+        let seed = new_server_seed();
+        let published = commitment(&seed);
+        let mut deck = Deck::new();
+        deck.shuffle_fair(&seed, &client_seeds());
+        assert_eq!(verify(&seed, &client_seeds(), Variant::Holdem, &published), Ok(deck.cards));
+    }
+
+    #[test]
+    fn verify_rejects_a_tampered_commitment() {
+        let seed = new_server_seed();
+        let mut published = commitment(&seed);
+        let last = if published.ends_with('0') { "1" } else { "0" };
+        published.replace_range(63.., last);
+        assert!(matches!(
+            verify(&seed, &client_seeds(), Variant::Holdem, &published),
+            Err(VerifyError::CommitmentMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn verify_rejects_a_swapped_server_seed() {
+        let seed = new_server_seed();
+        let published = commitment(&seed);
+        let mut other = seed;
+        other[0] ^= 1;
+        assert!(verify(&other, &client_seeds(), Variant::Holdem, &published).is_err());
+    }
+
+    #[test]
+    fn client_seeds_change_the_shuffle() {
+        let seed = [7u8; 32];
+        let (mut a, mut b) = (Deck::new(), Deck::new());
+        a.shuffle_fair(&seed, &["ab".to_string(), "c".to_string()]);
+        b.shuffle_fair(&seed, &["a".to_string(), "bc".to_string()]);
+        assert_ne!(a.cards, b.cards);
+    }
+
+    #[test]
+    fn shuffle_starts_from_the_documented_order() {
+        let mut deck = Deck::for_variant(Variant::ShortDeck);
+        deck.reset();
+        let order: Vec<String> = deck.cards.iter().take(10).map(|c| c.as_string()).collect();
+        assert_eq!(order, ["Ac", "6c", "7c", "8c", "9c", "Tc", "Jc", "Qc", "Kc", "Ad"]);
+    }
+}
@@ -1,18 +1,32 @@
 use std::borrow::BorrowMut;
 use std::collections::HashMap;
 use std::error::Error;
-use std::rc::Rc;
-use core::cell::RefCell;
+use std::fmt;
 
 use aya_poker::base::ParseError;
-use rand::Rng;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use clap::Parser;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use aya_poker::base::Hand as AyaHand;
 use aya_poker::base::Card as AyaCard;
-pub mod aya;
-use aya::equity_calculator;
-
-#[derive(Debug, Clone)]
+pub mod cardset;
+pub mod combos;
+pub mod equity;
+pub mod eval;
+pub mod fair;
+pub mod nuts;
+pub mod outs;
+pub mod parse;
+pub mod range;
+pub mod showdown;
+pub mod starting_hand;
+pub mod strength;
+pub mod texture;
+use cardset::CardSet;
+use eval::{BestHand, HandRank};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 
 // there is a difference between the players field in this struct and the internal Game.players
 // by using a Hashmap to keep track of player turn and SB/BB chip updating through a key index
@@ -25,6 +39,9 @@ pub struct GameMaster {
     pub player_log: Vec<PlayerDecisions>,
     pub turn_index: usize,
     pub active_player_count: usize,
+    // fixed cards for replaying a known hand, keyed by seat index
+    pub preset_hole_cards: HashMap<usize, Hand>,
+    pub preset_board: Vec<Card>,
 }
 impl GameMaster {
     pub fn new(g: Game, vp: Vec<Player>) -> Self {
@@ -42,25 +59,31 @@ impl GameMaster {
             small_blind: Some(10),
             big_blind: Some(20),
             bb_position: 1,
-            player_log: player_log,
+            player_log,
             turn_index: 2,
-            active_player_count: size
-
+            active_player_count: size,
+            preset_hole_cards: HashMap::new(),
+            preset_board: Vec::new(),
         }
     }
+    // seed for the following hand, so a seeded session replays the same run of hands
+    pub fn next_hand_seed(&self) -> Option<u64> {
+        self.gamestate.seed.map(|s| s.wrapping_add(1))
+    }
     pub fn init(&mut self) -> Result<Self, Box<dyn Error>> {
         self.initial_blinds()?;
         
         self.initial_deal()?;
+        self.update_equity()?;
 
         self.start_turn()?;
 
 
         // while self.player_log.len() < self.active_player_count {
         //     // appends to player log, which will eventually equal the amount of active players to 
-        //     self.decision_event();
+        //     self._decision_event();
         // }
-        // self.decision_event();
+        // self._decision_event();
         self.deal_flop()?;
 
         // self.deal_turn_or_river()?;
@@ -85,9 +108,55 @@ impl GameMaster {
 
         for p in &self.players {
             println!("PLAYER HAND: {:?} \n", p);
+            if let Ok(best) = self.gamestate.best_hand(p) {
+                println!("BEST HAND: {} \n", best);
+            }
         }
     }
+    // gives a seat fixed hole cards instead of dealing them, call before init
+    // replacing an earlier preset shuffles its cards back into the deck
+    pub fn set_hole_cards(&mut self, seat: usize, hand: Hand) -> Result<(), Box<dyn Error>> {
+        if seat >= self.players.len() {
+            return Err(format!("no player in seat {}", seat).into());
+        }
+        if hand.cards.len() != 2 {
+            return Err(format!("hole cards need 2 cards, got {}", hand.cards.len()).into());
+        }
+        if let Some(old) = self.preset_hole_cards.remove(&seat) {
+            self.return_to_deck(old.cards);
+        }
+        self.gamestate.deck.remove(&hand.cards)?;
+        self.preset_hole_cards.insert(seat, hand);
+        Ok(())
+    }
+
+    // fixes the first board cards in order (flop, turn, river), the rest are dealt as usual
+    pub fn set_board(&mut self, cards: Vec<Card>) -> Result<(), Box<dyn Error>> {
+        if cards.len() > 5 {
+            return Err(format!("a board holds 5 cards, got {}", cards.len()).into());
+        }
+        let old = std::mem::take(&mut self.preset_board);
+        self.return_to_deck(old);
+        self.gamestate.deck.remove(&cards)?;
+        self.preset_board = cards;
+        Ok(())
+    }
+
     // INTERNAL FUNCTIONS
+    // cards from a replaced preset go back in and the deck is shuffled again,
+    // left on top they would be the next cards dealt, in a known order
+    fn return_to_deck(&mut self, cards: Vec<Card>) {
+        if cards.is_empty() {
+            return;
+        }
+        let deck = &mut self.gamestate.deck;
+        deck.cards.extend(cards);
+        deck.deck_count = deck.cards.len();
+        match self.gamestate.seed {
+            Some(s) => deck.shuffle_seeded(s),
+            None => deck.shuffle(),
+        }
+    }
 
     fn start_turn(&mut self) -> Result<(), Box<dyn Error>> {
         assert_eq!(self.turn_index, self.bb_position+1);
@@ -102,8 +171,12 @@ impl GameMaster {
 
     fn initial_deal(&mut self) -> Result<(), Box<dyn Error>> {
         // DEAL OUT PLAYER HANDS
-        for p in &mut self.players {
-            let rng_hand = self.gamestate.deck.deal(2).map(|cards| Hand::new(cards));
+        for (seat, p) in self.players.iter_mut().enumerate() {
+            p.folded = false;
+            let rng_hand = match self.preset_hole_cards.get(&seat) {
+                Some(preset) => Some(Ok(preset.clone())),
+                None => self.gamestate.deck.deal(2).map(Hand::new),
+            };
 
             if let Some(h) = rng_hand {
                 match h {
@@ -123,11 +196,11 @@ impl GameMaster {
     fn initial_blinds(&mut self) -> Result<(), Box<dyn Error>> {
         let bb = self.players.get_mut(self.bb_position);
         if let Some(first_big) = bb {
-            first_big.chips -= self.big_blind.expect("BIG BLIND NOT FOUND");
+            first_big.commit_chips(self.big_blind.expect("BIG BLIND NOT FOUND"));
         }
         let sb = self.players.get_mut(self.bb_position-1);
         if let Some(first_small) = sb {
-            first_small.chips -= self.small_blind.expect("SMALL BLIND NOT FOUND");
+            first_small.commit_chips(self.small_blind.expect("SMALL BLIND NOT FOUND"));
         }
         Ok(())
 
@@ -136,42 +209,26 @@ impl GameMaster {
         // burn a card
         let _ = &self.gamestate.deck.deal(1); // does not need to be assigned to a hand like below
 
-        let flop = self.gamestate.deck.deal(3).map(|cards| Hand::new(cards));
+        let flop = self.deal_board(3).map(Hand::new);
         
         // ADD FLOP TO GAME OBJECT
         if let Some(h) = flop {
             match h {
                 Ok(h) => {
-                    self.gamestate.table_cards = Some(h.clone().cards);
-                    let p = self.players[0].clone().hand.unwrap();
-                    let o = self.players[1].clone().hand.unwrap();
-
-                    let equity = equity_calculator(
-                        &p.to_aya_sim().unwrap(), 
-                        &o.to_aya_sim().unwrap(),
-                        &h.to_aya_sim().unwrap()
-                    );
-
-                    println!(
-                        "{} has {:.1}% equity on {:?} against {}.",
-                        p.to_string(),
-                        100.0 * equity,
-                        h.cards,
-                        o.to_string()
-                    );
+                    self.gamestate.table_cards = Some(h.cards);
                 }
                 Err(err) => {
                     eprintln!("{:?}", err);
                 }
             }
         }
-        Ok(())
+        self.update_equity()
     }
 
     pub fn deal_turn_or_river(&mut self) -> Result<(), Box<dyn Error>> {
         //burn card
         let _ = &self.gamestate.deck.deal(1);
-        let turn = self.gamestate.deck.deal(1).map(|cards| Hand::new(cards));
+        let turn = self.deal_board(1).map(Hand::new);
         
         // ADD FLOP TO GAME OBJECT
         if let Some(h) = turn {
@@ -189,9 +246,16 @@ impl GameMaster {
             }
         }
 
-        Ok(())
+        self.update_equity()
     }
-    fn decision_event(&mut self) {
+    // next n board cards, preset board cards are used up before dealing from the deck
+    fn deal_board(&mut self, n: usize) -> Option<Vec<Card>> {
+        let on_table = self.gamestate.table_cards.as_ref().map_or(0, |t| t.len());
+        let mut cards: Vec<Card> = self.preset_board.iter().skip(on_table).take(n).copied().collect();
+        cards.extend(self.gamestate.deck.deal(n - cards.len())?);
+        Some(cards)
+    }
+    fn _decision_event(&mut self) {
 
     }
 }
@@ -200,7 +264,7 @@ pub struct DecisionHandler {
 
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum PlayerDecisions {
     Call,
     Bet,
@@ -231,7 +295,8 @@ by the total number of possible combinations -> 1,326:
 1 in 17, or 5.88%.
  */
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+// cards sort by rank first and suit second
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct Card {
     pub rank: &'static Rank,
     pub suit: &'static Suit,
@@ -246,19 +311,93 @@ impl Card {
         format!("{}{}", self.rank.to_string(), self.suit.to_string())
     }
 
+    // dense 0..52 index in sort order, 2c = 0, 2d = 1 .. As = 51, for keying lookup tables
+    pub fn index(&self) -> u8 {
+        (self.rank.high_value() - 2) * 4 + self.suit.index()
+    }
+
+    pub fn from_index(index: u8) -> Option<Card> {
+        if index >= 52 {
+            return None;
+        }
+        Some(Card::new(&Rank::ALL[(index / 4) as usize], &Suit::ALL[(index % 4) as usize]))
+    }
+
 }
 
-#[derive(Debug, Clone)]
+// cards go over the wire as their short text form ("Ah") so the
+// &'static rank and suit references can be rebuilt through FromStr
+impl Serialize for Card {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.as_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Card {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        text.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+// which deck and hand ranking rules a table plays with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum Variant {
+    #[default]
+    Holdem,
+    // 36 cards, six through ace, a flush beats a full house and A-6-7-8-9 is a straight
+    ShortDeck,
+}
+
+impl Variant {
+    pub fn deck_size(&self) -> usize {
+        match self {
+            Variant::Holdem => 52,
+            Variant::ShortDeck => 36,
+        }
+    }
+
+    pub fn uses_rank(&self, rank: &Rank) -> bool {
+        match self {
+            Variant::Holdem => true,
+            Variant::ShortDeck => rank.high_value() >= 6,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Deck {
     pub cards: Vec<Card>,
     pub deck_count: usize,
+    pub variant: Variant,
     // ranks: &'a [Rank],
     // suits: &'a [Suit],
 }
 
+impl Default for Deck {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Deck {
     //ranks: &'a [Rank], suits: &'a [Suit]
     pub fn new() -> Deck {
+        Deck::for_variant(Variant::Holdem)
+    }
+
+    pub fn for_variant(variant: Variant) -> Deck {
+        let mut deck = Deck {
+            cards: Vec::with_capacity(variant.deck_size()),
+            deck_count: 0,
+            variant,
+        };
+        deck.reset();
+        deck
+    }
+
+    // puts every card of the variant back in order, reusing the existing allocation
+    pub fn reset(&mut self) {
         let ranks = &[
             Rank::Ace,
             Rank::Two,
@@ -275,18 +414,17 @@ impl Deck {
             Rank::King,
         ];
         let suits = &[Suit::Clubs, Suit::Diamonds, Suit::Hearts, Suit::Spades];
-        let mut cards_vec = Vec::new();
+        self.cards.clear();
         for suit in suits {
             for rank in ranks {
-                cards_vec.push(Card::new(rank, suit));
+                if self.variant.uses_rank(rank) {
+                    self.cards.push(Card::new(rank, suit));
+                }
             }
         }
-        let count = cards_vec.len();
-        assert_eq!(count, 52);
-        Deck { 
-            cards: cards_vec,
-            deck_count: count, //this will mutate as the cards are dealt
-        }
+        let count = self.cards.len();
+        assert_eq!(count, self.variant.deck_size());
+        self.deck_count = count; //this will mutate as the cards are dealt
     }
 
     // shows the state of current deck
@@ -299,7 +437,15 @@ impl Deck {
         self.cards
     }
     pub fn shuffle(&mut self) {
-        let mut rng = rand::thread_rng();
+        self.shuffle_with(&mut rand::thread_rng());
+    }
+
+    // the same seed always produces the same deck order
+    pub fn shuffle_seeded(&mut self, seed: u64) {
+        self.shuffle_with(&mut StdRng::seed_from_u64(seed));
+    }
+
+    pub fn shuffle_with<R: Rng + ?Sized>(&mut self, rng: &mut R) {
         for i in (1..self.cards.len()).rev() {
             let j = rng.gen_range(0..i + 1);
             self.cards.swap(i, j);
@@ -315,14 +461,54 @@ impl Deck {
         }
     }
 
-    fn get_suits() -> Vec<Suit> {
+    // same as deal but hands back a bitmask instead of allocating a new Vec
+    pub fn deal_set(&mut self, n: usize) -> Option<CardSet> {
+        if n > self.cards.len() {
+            return None;
+        }
+        let mut set = CardSet::new();
+        for _ in 0..n {
+            if let Some(c) = self.cards.pop() {
+                set.add(c);
+            }
+        }
+        Some(set)
+    }
+
+    // takes dead cards out of the deck, nothing is removed if any of them is already gone
+    pub fn remove(&mut self, dead: &[Card]) -> Result<(), DeckError> {
+        for (i, c) in dead.iter().enumerate() {
+            if dead[..i].contains(c) {
+                return Err(DeckError::DuplicateCard(*c));
+            }
+            if !self.cards.contains(c) {
+                return Err(DeckError::CardNotInDeck(*c));
+            }
+        }
+        self.cards.retain(|c| !dead.contains(c));
+        self.deck_count = self.cards.len();
+        Ok(())
+    }
+
+    // fresh unshuffled deck without the given cards
+    pub fn from_excluding(dead: &[Card]) -> Result<Deck, DeckError> {
+        Deck::from_excluding_with(dead, Variant::Holdem)
+    }
+
+    pub fn from_excluding_with(dead: &[Card], variant: Variant) -> Result<Deck, DeckError> {
+        let mut deck = Deck::for_variant(variant);
+        deck.remove(dead)?;
+        Ok(deck)
+    }
+
+    fn _get_suits() -> Vec<Suit> {
         vec![Suit::Clubs, Suit::Diamonds, Suit::Hearts, Suit::Spades]
     }
 
 }
 
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Hand {
     pub cards: Vec<Card>,
 }
@@ -337,14 +523,16 @@ impl Hand {
         self.cards[0].rank == self.cards[1].rank
     }
 
-    pub fn to_string(&self) -> String {
-        self.cards
-            .iter()
-            .map(|card| format!("{}{}", card.rank.to_string(), card.suit.to_string()))
-            .collect::<Vec<String>>()
-            .join(" ")
-    }
     
+    // ranks the best five cards, needs 5 to 7 cards in the hand
+    pub fn evaluate(&self) -> Result<HandRank, HandError> {
+        eval::evaluate(&self.cards)
+    }
+
+    pub fn evaluate_with(&self, variant: Variant) -> Result<HandRank, HandError> {
+        eval::evaluate_with(&self.cards, variant)
+    }
+
     pub fn to_aya_sim(&self) -> Result<AyaHand, ParseError> {
         self.cards.iter()
             .map(|c| c.as_string().parse::<AyaCard>())
@@ -353,24 +541,70 @@ impl Hand {
 }
 
 
+impl fmt::Display for Hand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let cards = self.cards
+            .iter()
+            .map(|card| card.as_string())
+            .collect::<Vec<String>>()
+            .join(" ");
+        write!(f, "{}", cards)
+    }
+}
+
+#[derive(Debug)]
+pub enum DeckError {
+    CardNotInDeck(Card),
+    DuplicateCard(Card),
+}
+
+impl fmt::Display for DeckError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeckError::CardNotInDeck(c) => write!(f, "{} is not in the deck", c.as_string()),
+            DeckError::DuplicateCard(c) => write!(f, "{} is listed more than once", c.as_string()),
+        }
+    }
+}
+
+impl Error for DeckError {}
+
 #[derive(Debug)]
 pub enum HandError {
     InvalidSize(usize),
+    DuplicateCard(Card),
+    // a card the variant's deck doesn't have, like a deuce in short deck
+    CardNotInVariant(Card),
+}
+
+impl fmt::Display for HandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HandError::InvalidSize(n) => write!(f, "invalid number of cards: {}", n),
+            HandError::DuplicateCard(c) => write!(f, "{} appears more than once", c.as_string()),
+            HandError::CardNotInVariant(c) => write!(f, "{} is not in this variant's deck", c.as_string()),
+        }
+    }
 }
 
+impl Error for HandError {}
+
 
 
 // GAME STRUCT
 
 
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Player {
     pub name: Option<String>,
     pub hand: Option<Hand>,
     pub chips: i32,
     pub hand_equity: f64,
     pub is_turn: bool,
+    // chips put into the pot this hand, used to build side pots at showdown
+    pub contributed: i32,
+    pub folded: bool,
 }
 impl Player {
     pub fn new(name: String) -> Self {
@@ -379,11 +613,23 @@ impl Player {
             hand: None,
             chips: 0,
             hand_equity: 0.0,
-            is_turn: false
+            is_turn: false,
+            contributed: 0,
+            folded: false,
         }
 
     }
 
+    // moves chips from the stack into the pot
+    pub fn commit_chips(&mut self, amount: i32) {
+        self.chips -= amount;
+        self.contributed += amount;
+    }
+
+    pub fn fold(&mut self) {
+        self.folded = true;
+    }
+
     pub fn add_hand(&mut self, h: Hand) {
         // self.hand.replace(Rc::new(RefCell::new(h)));
         self.hand.replace(h);
@@ -439,19 +685,34 @@ impl Player {
 
     }
 }
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Game {
     pub deck: Box<Deck>,
     pub player_ct: Option<usize>,
     pub players: Option<Vec<Player>>,
     pub table_cards: Option<Vec<Card>>,
-    pub hand_list: Option<Vec<Hand>>
+    pub hand_list: Option<Vec<Hand>>,
+    // deck shuffle seed, None shuffles from thread_rng
+    pub seed: Option<u64>,
+    pub variant: Variant,
+}
+impl Default for Game {
+    fn default() -> Self {
+        Self::new(None)
+    }
 }
 impl Game {
-    pub fn new() -> Self {
+    pub fn new(seed: Option<u64>) -> Self {
+        Self::new_variant(seed, Variant::Holdem)
+    }
+
+    pub fn new_variant(seed: Option<u64>, variant: Variant) -> Self {
         let player_list = Vec::<Player>::new();
-        let mut d = Box::new(Deck::new());
-        d.shuffle();
+        let mut d = Box::new(Deck::for_variant(variant));
+        match seed {
+            Some(s) => d.shuffle_seeded(s),
+            None => d.shuffle(),
+        }
         let n_players = 8;
         Self {
             deck: d,
@@ -459,7 +720,18 @@ impl Game {
             players: Some(player_list),
             table_cards: None,
             hand_list: None,
+            seed,
+            variant,
+        }
+    }
+
+    // strongest five cards the player can make with the current table cards
+    pub fn best_hand(&self, p: &Player) -> Result<BestHand, HandError> {
+        let mut cards: Vec<Card> = p.hand.as_ref().map(|h| h.cards.clone()).unwrap_or_default();
+        if let Some(table) = &self.table_cards {
+            cards.extend(table.iter().copied());
         }
+        eval::best_five_with(&cards, self.variant)
     }
 
     pub fn show_players(self) {
@@ -477,8 +749,6 @@ impl Game {
    
         
     }
-    // LATER
-    // pub fn winning_hand(self) {}
     pub fn select_cards_svg(cards: Vec<Card>) -> Vec<String> {
         let mut card_file_paths: Vec<String> = Vec::new();
 
@@ -532,14 +802,17 @@ impl Game {
 
 
 
-#[derive(Debug, PartialEq, Clone, Parser)]
+// suits are declared in the same order as the CardSet lanes, clubs first
+#[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Clone, Copy, Parser, Serialize, Deserialize)]
 pub enum Suit {
-    Hearts,
-    Diamonds,
     Clubs,
+    Diamonds,
+    Hearts,
     Spades,
 }
 impl Suit {
+    pub const ALL: &'static [Suit; 4] = &[Suit::Clubs, Suit::Diamonds, Suit::Hearts, Suit::Spades];
+
     pub fn to_string(&self) -> &'static str {
         match self {
             Suit::Hearts => "h",
@@ -548,12 +821,17 @@ impl Suit {
             Suit::Spades => "s",
         }
     }
+
+    // 0 = clubs, 1 = diamonds, 2 = hearts, 3 = spades
+    pub fn index(&self) -> u8 {
+        *self as u8
+    }
 }
 
 
-#[derive(Debug, PartialEq)]
+// ranks are declared low to high so the derived ordering puts the ace on top
+#[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Clone, Copy, Serialize, Deserialize)]
 pub enum Rank {
-    Ace,
     Two,
     Three,
     Four,
@@ -566,9 +844,27 @@ pub enum Rank {
     Jack,
     Queen,
     King,
+    Ace,
 }
 
 impl Rank {
+    // borrowing from ALL hands out the &'static references a Card needs
+    pub const ALL: &'static [Rank; 13] = &[
+        Rank::Two,
+        Rank::Three,
+        Rank::Four,
+        Rank::Five,
+        Rank::Six,
+        Rank::Seven,
+        Rank::Eight,
+        Rank::Nine,
+        Rank::Ten,
+        Rank::Jack,
+        Rank::Queen,
+        Rank::King,
+        Rank::Ace,
+    ];
+
     pub fn to_string(&self) -> &'static str {
         match self {
             Rank::Two => "2",
@@ -586,4 +882,149 @@ impl Rank {
             Rank::Ace => "A",
         }
     }
-}
\ No newline at end of file
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Rank::Two => "Two",
+            Rank::Three => "Three",
+            Rank::Four => "Four",
+            Rank::Five => "Five",
+            Rank::Six => "Six",
+            Rank::Seven => "Seven",
+            Rank::Eight => "Eight",
+            Rank::Nine => "Nine",
+            Rank::Ten => "Ten",
+            Rank::Jack => "Jack",
+            Rank::Queen => "Queen",
+            Rank::King => "King",
+            Rank::Ace => "Ace",
+        }
+    }
+
+    pub fn plural_name(&self) -> &'static str {
+        match self {
+            Rank::Two => "Twos",
+            Rank::Three => "Threes",
+            Rank::Four => "Fours",
+            Rank::Five => "Fives",
+            Rank::Six => "Sixes",
+            Rank::Seven => "Sevens",
+            Rank::Eight => "Eights",
+            Rank::Nine => "Nines",
+            Rank::Ten => "Tens",
+            Rank::Jack => "Jacks",
+            Rank::Queen => "Queens",
+            Rank::King => "Kings",
+            Rank::Ace => "Aces",
+        }
+    }
+
+    // 2 for a deuce up to 14 for an ace
+    pub fn high_value(&self) -> u8 {
+        *self as u8 + 2
+    }
+
+    // same as high_value except the ace counts as 1, for wheels and ace-low games
+    pub fn low_value(&self) -> u8 {
+        match self {
+            Rank::Ace => 1,
+            _ => self.high_value(),
+        }
+    }
+
+    pub fn from_value(value: u8) -> Option<&'static Rank> {
+        match value {
+            1 => Some(&Rank::Ace),
+            2..=14 => Some(&Rank::ALL[value as usize - 2]),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cards(s: &str) -> Vec<Card> {
+        s.parse::<Hand>().unwrap().cards
+    }
+
+    #[test]
+    fn remove_reports_duplicates_and_missing_cards() {
+        let mut deck = Deck::new();
+        let ace: Card = "Ah".parse().unwrap();
+        assert!(matches!(deck.remove(&[ace, ace]), Err(DeckError::DuplicateCard(_))));
+        deck.remove(&cards("Ah")).unwrap();
+        assert!(matches!(deck.remove(&cards("Ah")), Err(DeckError::CardNotInDeck(_))));
+        assert_eq!(deck.cards.len(), 51);
+    }
+
+    #[test]
+    fn from_excluding_with_builds_the_variant_deck() {
+        let deck = Deck::from_excluding_with(&cards("As6c"), Variant::ShortDeck).unwrap();
+        assert_eq!(deck.cards.len(), 34);
+        assert!(Deck::from_excluding_with(&cards("2c"), Variant::ShortDeck).is_err());
+    }
+
+    fn dealt_cards(gm: &GameMaster) -> (Vec<Option<Vec<Card>>>, Option<Vec<Card>>) {
+        let hands = gm.players.iter().map(|p| p.hand.as_ref().map(|h| h.cards.clone())).collect();
+        (hands, gm.gamestate.table_cards.clone())
+    }
+
+    fn seeded_game(seed: u64) -> GameMaster {
+        let players = (0..4).map(|i| Player::new(format!("p{}", i))).collect();
+        let mut gm = GameMaster::new(Game::new(Some(seed)), players);
+        gm.init().unwrap();
+        gm
+    }
+
+    #[test]
+    fn same_seed_deals_the_same_cards() {
+        let (mut a, mut b) = (Deck::new(), Deck::new());
+        a.shuffle_seeded(42);
+        b.shuffle_seeded(42);
+        assert_eq!(a.cards, b.cards);
+        b.shuffle_seeded(43);
+        assert_ne!(a.cards, b.cards);
+
+        let (first, second) = (seeded_game(42), seeded_game(42));
+        assert_eq!(dealt_cards(&first), dealt_cards(&second));
+        assert_eq!(first.gamestate.deck.cards, second.gamestate.deck.cards);
+        assert_ne!(dealt_cards(&first), dealt_cards(&seeded_game(43)));
+    }
+
+    #[test]
+    fn game_snapshot_round_trips_through_json() {
+        let players = (0..3).map(|i| Player::new(format!("p{}", i))).collect();
+        let mut gm = GameMaster::new(Game::new_variant(Some(9), Variant::ShortDeck), players);
+        gm.set_hole_cards(1, "AhKh".parse().unwrap()).unwrap();
+        gm.set_board(cards("QhJh")).unwrap();
+        gm.init().unwrap();
+
+        let json = serde_json::to_value(&gm).unwrap();
+        let restored: GameMaster = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&restored).unwrap(), json);
+        // cards come back as the same &'static ranks and suits, presets keep their seats
+        assert_eq!(dealt_cards(&restored), dealt_cards(&gm));
+        assert_eq!(restored.gamestate.deck.cards, gm.gamestate.deck.cards);
+        assert_eq!(restored.preset_hole_cards[&1].cards, cards("AhKh"));
+        assert_eq!(restored.preset_board, cards("QhJh"));
+        assert_eq!(restored.gamestate.variant, Variant::ShortDeck);
+    }
+
+    #[test]
+    fn replaced_presets_are_shuffled_back_in() {
+        let players = vec![Player::new("a".to_string()), Player::new("b".to_string())];
+        let mut gm = GameMaster::new(Game::new(Some(3)), players);
+        gm.set_hole_cards(0, "AhKh".parse().unwrap()).unwrap();
+        gm.set_board(cards("2c3c4c")).unwrap();
+        gm.set_hole_cards(0, "QsQd".parse().unwrap()).unwrap();
+        gm.set_board(cards("5d6d7d")).unwrap();
+
+        let deck = &gm.gamestate.deck.cards;
+        assert_eq!(deck.len(), 52 - 5);
+        let top: Vec<Card> = deck[deck.len() - 5..].to_vec();
+        assert_ne!(top, cards("2c3c4cAhKh"));
+        assert!(cards("AhKh2c3c4c").iter().all(|c| deck.contains(c)));
+    }
+}
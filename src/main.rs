@@ -1,34 +1,142 @@
 use clap::Parser;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use std::fmt;
+use std::thread;
 use std::time::Instant;
-use holdem::{Rank, Deck, Hand};
+use holdem::{Rank, Deck};
+use holdem::cardset::CardSet;
+use holdem::eval::{evaluate_set, HandCategory, HandRank};
+use holdem::equity::{range_equity_with, EquityResult, PlayerEquity, MONTE_CARLO_TRIALS};
+use holdem::range::Range;
+use holdem::starting_hand::StartingHand;
+use holdem::Variant;
 /// arg for number of simulations to run
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 struct Args {
     /// Number of hands dealt in a sim
-    #[arg(short = 'n', default_value_t = 1000000)]
+    #[arg(short = 'n', default_value_t = 1000000, value_parser = clap::value_parser!(u32).range(1..))]
     num_simulations: u32,
     ///Amount of sims to run
     #[arg(short = 'r', default_value_t = 1, required = false)]
     repeat_n_sims: u32,
+    ///Seed for the shuffles, the same seed deals the same hands
+    #[arg(short = 's', long = "seed")]
+    seed: Option<u64>,
+    ///Starting hand to track, e.g. AKs, T9o or 77
+    #[arg(long = "hand", conflicts_with = "range")]
+    hand: Option<StartingHand>,
+    ///Range to track, e.g. "QQ+,AK", weights are ignored
+    #[arg(long = "range")]
+    range: Option<Range>,
+    ///Players dealt in at the table, a tracked hand sits in seat 1
+    #[arg(short = 'p', long = "players", default_value_t = 2, value_parser = clap::value_parser!(u8).range(2..=10))]
+    players: u8,
+    ///Worker threads, defaults to one per core. The same seed and thread count give the same results
+    #[arg(short = 't', long = "threads")]
+    threads: Option<usize>,
+    ///Keep dealing batches of -n hands until every 95% confidence interval is within this of its estimate, e.g. 0.001 for ±0.1%
+    #[arg(long = "precision", value_parser = parse_precision)]
+    precision: Option<f64>,
+}
+
+// 95% confidence intervals reach 1.96 standard errors either side of the estimate
+const Z_95: f64 = 1.96;
+// a precision mode run stops after this many batches even if it hasn't converged
+const MAX_BATCHES: u32 = 1000;
+
+// a half width has to be a positive fraction, anything else can never be reached
+fn parse_precision(s: &str) -> Result<f64, String> {
+    let precision: f64 = s.parse().map_err(|_| format!("{} is not a number", s))?;
+    if precision > 0.0 && precision < 1.0 {
+        Ok(precision)
+    } else {
+        Err(format!("{} is not between 0 and 1", s))
+    }
+}
+
+// a simulated frequency or average with its standard error
+#[derive(Copy, Clone)]
+struct Estimate {
+    value: f64,
+    std_error: f64,
+}
+impl Estimate {
+    // share of n trials that hit
+    fn proportion(hits: f64, n: u64) -> Self {
+        let n = n.max(1) as f64;
+        let p = hits / n;
+        Estimate { value: p, std_error: (p * (1.0 - p) / n).sqrt() }
+    }
+
+    // average of n samples, from their sum and their sum of squares
+    fn mean(sum: f64, sum_sq: f64, n: u64) -> Self {
+        let n = n.max(1) as f64;
+        let mean = sum / n;
+        let variance = if n > 1.0 { (sum_sq / n - mean * mean).max(0.0) * n / (n - 1.0) } else { 0.0 };
+        Estimate { value: mean, std_error: (variance / n).sqrt() }
+    }
+
+    fn half_width(&self) -> f64 {
+        Z_95 * self.std_error
+    }
+
+    // something never seen yet has no usable interval, so it hasn't converged either
+    fn converged(&self, precision: f64) -> bool {
+        self.value > 0.0 && self.half_width() <= precision
+    }
+}
+impl fmt::Display for Estimate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = format!(
+            "{:.4}% ±{:.4} (se {:.4})",
+            100.0 * self.value,
+            100.0 * self.half_width(),
+            100.0 * self.std_error
+        );
+        write!(f, "{:>32}", text)
+    }
 }
 
 fn main() {
     let args = Args::parse();
     let start_time = Instant::now();
+    // the master rng hands out a seed to every worker, so a seeded run is fully reproducible
+    // for the same thread count. unseeded runs print the seed they picked
+    let seed = args.seed.unwrap_or_else(|| {
+        let seed = StdRng::from_entropy().gen();
+        println!("seed: {}", seed);
+        seed
+    });
+    let mut rng = StdRng::seed_from_u64(seed);
+    let threads = args
+        .threads
+        .unwrap_or_else(|| thread::available_parallelism().map_or(1, |n| n.get()))
+        .max(1);
+
+    // tracked hand or range, every combo counts fully whatever its weight
+    let target: Option<Range> = match (args.hand, args.range) {
+        (Some(hand), _) => Some(Range::from(hand)),
+        (None, Some(range)) => Some(range.combos().map(|(c, _)| c).collect()),
+        (None, None) => None,
+    };
+    let reference = target.as_ref().and_then(|t| {
+        let trials = reference_trials(t, args.num_simulations, args.precision);
+        target_reference(t, args.players as usize - 1, trials, threads, &mut rng)
+    });
 
     // optional sims comes from the -r flag = "repeat"
     let optional_sims: u32 = args.repeat_n_sims;
     // run one simulation
     if optional_sims == 1 {
-        simulation_builder(args.num_simulations);
+        simulation_builder(args.num_simulations, &mut rng, threads, target.as_ref(), args.players as usize, reference.as_ref(), args.precision);
     // optional arg -r was passed to repeat a custom simulation 'n' times
     } else {
         //repeat a custom simulation with n-hands optional and checking for any type of hand
         for sample_num in 0..optional_sims {
             println!("RUNNING SIMULATION #{:?}\n", sample_num + 1);
-            simulation_builder(args.num_simulations);
+            simulation_builder(args.num_simulations, &mut rng, threads, target.as_ref(), args.players as usize, reference.as_ref(), args.precision);
         }
     }
 
@@ -45,9 +153,13 @@ fn track_runtime(pass_start: Instant) {
 // and increments the sim pair results array through a match block
 fn track_simulation_pairs(
     mut pair_distribution: MonteCarloPairDistribution,
-    h: Hand,
+    h: CardSet,
 ) -> MonteCarloPairDistribution {
-    match (h.cards[0].rank, h.cards[1].rank) {
+    let mut cards = h.iter();
+    let (Some(first), Some(second)) = (cards.next(), cards.next()) else {
+        return pair_distribution;
+    };
+    match (first.rank, second.rank) {
         (Rank::Two, Rank::Two) => pair_distribution.deuces += 1,
         (Rank::Three, Rank::Three) => pair_distribution.threes += 1,
         (Rank::Four, Rank::Four) => pair_distribution.fours += 1,
@@ -64,13 +176,13 @@ fn track_simulation_pairs(
         _ => {} //skip any non-pair hand combos
     }
     //update pair distribution tracker and return it
-    return pair_distribution;
+    pair_distribution
 }
 //STRUCT AND IMPL LOGIC
 #[derive(Default, Copy, Clone)]
 //Default value of usize is 0
 struct MonteCarloPairDistribution {
-    total_hands: u32,
+    total_hands: u64,
     deuces: usize,
     threes: usize,
     fours: usize,
@@ -85,48 +197,522 @@ struct MonteCarloPairDistribution {
     kings: usize,
     aces: usize,
 }
+impl MonteCarloPairDistribution {
+    fn merge(&mut self, other: &MonteCarloPairDistribution) {
+        self.total_hands += other.total_hands;
+        self.deuces += other.deuces;
+        self.threes += other.threes;
+        self.fours += other.fours;
+        self.fives += other.fives;
+        self.sixes += other.sixes;
+        self.sevens += other.sevens;
+        self.eights += other.eights;
+        self.nines += other.nines;
+        self.tens += other.tens;
+        self.jacks += other.jacks;
+        self.queens += other.queens;
+        self.kings += other.kings;
+        self.aces += other.aces;
+    }
+}
+impl MonteCarloPairDistribution {
+    fn counts(&self) -> [(&'static str, usize); 13] {
+        [
+            ("TWOS", self.deuces),
+            ("THREES", self.threes),
+            ("FOURS", self.fours),
+            ("FIVES", self.fives),
+            ("SIXES", self.sixes),
+            ("SEVENS", self.sevens),
+            ("EIGHTS", self.eights),
+            ("NINES", self.nines),
+            ("TENS", self.tens),
+            ("JACKS", self.jacks),
+            ("QUEENS", self.queens),
+            ("KINGS", self.kings),
+            ("ACES", self.aces),
+        ]
+    }
+
+    fn estimates(&self) -> Vec<Estimate> {
+        self.counts().iter().map(|(_, n)| Estimate::proportion(*n as f64, self.total_hands)).collect()
+    }
+}
 impl fmt::Display for MonteCarloPairDistribution {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        writeln!(
-            f,
-            "TWOS: {}/{}\n THREES: {}/{}\n FOURS: {}/{}\n FIVES: {}/{}\n SIXES: {}/{}\n SEVENS: {}/{}\n EIGHTS: {}/{}\n NINES: {}/{}\n TENS: {}/{}\n JACKS: {}/{}\n QUEENS: {}/{}\n KINGS: {}/{}\n ACES: {}/{}",
-            self.deuces, self.total_hands, self.threes, self.total_hands, self.fours, self.total_hands, self.fives, self.total_hands, self.sixes, self.total_hands, self.sevens, self.total_hands, self.eights, self.total_hands, self.nines, self.total_hands, self.tens, self.total_hands, self.kings, self.total_hands, self.queens, self.total_hands, self.jacks, self.total_hands, self.aces, self.total_hands
-        )
+        // each specific pair is dealt 6 ways out of 1326
+        let expected = 100.0 * 6.0 / 1326.0;
+        writeln!(f, "{:<8} {:>12} {:>32} {:>10}", "PAIR", "COUNT", "SIM % ±95% CI (se)", "EXPECTED %")?;
+        for ((name, count), estimate) in self.counts().iter().zip(self.estimates()) {
+            writeln!(f, "{:<8} {:>12} {} {:>9.4}%", name, count, estimate, expected)?;
+        }
+        Ok(())
     }
 }
 
 
+// every category from high card up, in the same order as HandCategory
+const CATEGORIES: [HandCategory; 9] = [
+    HandCategory::HighCard,
+    HandCategory::OnePair,
+    HandCategory::TwoPair,
+    HandCategory::ThreeOfAKind,
+    HandCategory::Straight,
+    HandCategory::Flush,
+    HandCategory::FullHouse,
+    HandCategory::FourOfAKind,
+    HandCategory::StraightFlush,
+];
+// exact number of 7-card hands making each category, out of C(52,7) = 133,784,560
+const SEVEN_CARD_HANDS: [u64; 9] = [
+    23_294_460,
+    58_627_800,
+    31_433_400,
+    6_461_620,
+    6_180_020,
+    4_047_644,
+    3_473_184,
+    224_848,
+    41_584,
+];
+const SEVEN_CARD_TOTAL: u64 = 133_784_560;
 
-fn simulation_builder(n_sims: u32) {
-    let mut pair_tracker = MonteCarloPairDistribution::default();
-    pair_tracker.total_hands = n_sims;
-    let mut deck = Box::new(Deck::new());
-    deck.shuffle();
-    // let mut pair_tracker = 0;
-    // let mut suit_tracker = 0;
-    // let mut rockets = 0;
+// final category of each seat's hole cards plus the full board, counted per category
+#[derive(Default, Clone)]
+struct MonteCarloCategoryDistribution {
+    total_hands: u64,
+    seats: Vec<[usize; 9]>,
+}
+impl MonteCarloCategoryDistribution {
+    fn merge(&mut self, other: &MonteCarloCategoryDistribution) {
+        self.total_hands += other.total_hands;
+        for (mine, theirs) in self.seats.iter_mut().zip(&other.seats) {
+            for (a, b) in mine.iter_mut().zip(theirs) {
+                *a += b;
+            }
+        }
+    }
+}
+impl MonteCarloCategoryDistribution {
+    fn estimates(&self) -> Vec<Estimate> {
+        self.seats
+            .iter()
+            .flat_map(|counts| counts.iter().map(|n| Estimate::proportion(*n as f64, self.total_hands)))
+            .collect()
+    }
+}
+impl fmt::Display for MonteCarloCategoryDistribution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (seat, counts) in self.seats.iter().enumerate() {
+            writeln!(f, "SEAT {}", seat + 1)?;
+            writeln!(f, "{:<16} {:>32} {:>10}", "CATEGORY", "SIM % ±95% CI (se)", "EXPECTED %")?;
+            for (i, category) in CATEGORIES.iter().enumerate().rev() {
+                writeln!(
+                    f,
+                    "{:<16} {} {:>9.4}%",
+                    category.name().to_uppercase(),
+                    Estimate::proportion(counts[i] as f64, self.total_hands),
+                    100.0 * SEVEN_CARD_HANDS[i] as f64 / SEVEN_CARD_TOTAL as f64
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// showdown results for every seat, random hands should each take 1/N of the pots
+#[derive(Default, Clone)]
+struct MonteCarloSeatResults {
+    total_hands: u64,
+    wins: Vec<u64>,
+    ties: Vec<u64>,
+    // pot share summed over every showdown, ties split evenly, and its square for the standard error
+    equity: Vec<f64>,
+    equity_sq: Vec<f64>,
+}
+impl MonteCarloSeatResults {
+    fn record(&mut self, ranks: &[HandRank]) {
+        let Some(best) = ranks.iter().max() else {
+            return;
+        };
+        let sharing = ranks.iter().filter(|r| *r == best).count();
+        for (seat, rank) in ranks.iter().enumerate() {
+            if rank != best {
+                continue;
+            }
+            if sharing == 1 {
+                self.wins[seat] += 1;
+            } else {
+                self.ties[seat] += 1;
+            }
+            let share = 1.0 / sharing as f64;
+            self.equity[seat] += share;
+            self.equity_sq[seat] += share * share;
+        }
+    }
+
+    fn merge(&mut self, other: &MonteCarloSeatResults) {
+        self.total_hands += other.total_hands;
+        for seat in 0..self.wins.len() {
+            self.wins[seat] += other.wins[seat];
+            self.ties[seat] += other.ties[seat];
+            self.equity[seat] += other.equity[seat];
+            self.equity_sq[seat] += other.equity_sq[seat];
+        }
+    }
+
+    // won, tied and equity for one seat
+    fn seat_estimates(&self, seat: usize) -> [Estimate; 3] {
+        [
+            Estimate::proportion(self.wins[seat] as f64, self.total_hands),
+            Estimate::proportion(self.ties[seat] as f64, self.total_hands),
+            Estimate::mean(self.equity[seat], self.equity_sq[seat], self.total_hands),
+        ]
+    }
+
+    fn estimates(&self) -> Vec<Estimate> {
+        (0..self.wins.len()).flat_map(|seat| self.seat_estimates(seat)).collect()
+    }
+}
+impl fmt::Display for MonteCarloSeatResults {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let expected = 100.0 / self.wins.len() as f64;
+        for seat in 0..self.wins.len() {
+            let [won, tied, equity] = self.seat_estimates(seat);
+            writeln!(f, "SEAT {}", seat + 1)?;
+            writeln!(f, "{:<8} {}", "WON", won)?;
+            writeln!(f, "{:<8} {}", "TIED", tied)?;
+            writeln!(f, "{:<8} {}   expected {:.4}%", "EQUITY", equity, expected)?;
+        }
+        Ok(())
+    }
+}
+
+// how a tracked hand or range did over the hands it was dealt
+#[derive(Default, Copy, Clone)]
+struct MonteCarloTargetTracker {
+    total_hands: u64,
+    opponents: usize,
+    // share of all 1326 combos in the target
+    dealt_expected: f64,
+    dealt: u64,
+    wins: u64,
+    ties: u64,
+    // pot share summed over every showdown, ties split evenly, and its square for the standard error
+    equity: f64,
+    equity_sq: f64,
+}
+impl MonteCarloTargetTracker {
+    // ranks of every seat at showdown, the target sits first
+    fn record(&mut self, ranks: &[HandRank]) {
+        let Some((ours, others)) = ranks.split_first() else {
+            return;
+        };
+        let Some(theirs) = others.iter().max() else {
+            return;
+        };
+        self.dealt += 1;
+        let share = if ours > theirs {
+            self.wins += 1;
+            1.0
+        } else if ours == theirs {
+            self.ties += 1;
+            1.0 / (1 + others.iter().filter(|r| *r == theirs).count()) as f64
+        } else {
+            0.0
+        };
+        self.equity += share;
+        self.equity_sq += share * share;
+    }
+
+    fn merge(&mut self, other: &MonteCarloTargetTracker) {
+        self.total_hands += other.total_hands;
+        self.dealt += other.dealt;
+        self.wins += other.wins;
+        self.ties += other.ties;
+        self.equity += other.equity;
+        self.equity_sq += other.equity_sq;
+    }
+
+    // dealt out of all hands, then won, tied and equity out of the hands it was dealt
+    fn estimates(&self) -> [Estimate; 4] {
+        [
+            Estimate::proportion(self.dealt as f64, self.total_hands),
+            Estimate::proportion(self.wins as f64, self.dealt),
+            Estimate::proportion(self.ties as f64, self.dealt),
+            Estimate::mean(self.equity, self.equity_sq, self.dealt),
+        ]
+    }
+
+    fn print(&self, reference: Option<&TargetReference>) {
+        let expected = |value: Option<&Estimate>| value.map_or(format!("{:>32}", "-"), |e| e.to_string());
+        let [dealt, won, tied, equity] = self.estimates();
+        let dealt_expected = Estimate { value: self.dealt_expected, std_error: 0.0 };
+        println!("target hand vs {} random opponent(s)", self.opponents);
+        println!("{:<8} {:>12} {:>32} {:>32}", "", "COUNT", "SIM % ±95% CI (se)", "REFERENCE % ±95% CI (se)");
+        println!("{:<8} {:>12} {} {}", "DEALT", self.dealt, dealt, dealt_expected);
+        println!("{:<8} {:>12} {} {}", "WON", self.wins, won, expected(reference.map(|r| &r.win)));
+        println!("{:<8} {:>12} {} {}", "TIED", self.ties, tied, expected(reference.map(|r| &r.tie)));
+        println!("{:<8} {:>12} {} {}", "EQUITY", "", equity, expected(reference.map(|r| &r.equity)));
+        match reference {
+            Some(r) if r.exact => println!("reference figures are exact"),
+            Some(r) => println!("reference figures are sampled estimates from {} equity calculator trials", r.samples),
+            None => println!("no reference figures for this table"),
+        }
+    }
+}
+
+// the target's win, tie and equity figures from the equity calculator, against full random ranges
+// preflop the calculator samples deals, so each figure carries its own standard error,
+// kept at about a tenth of the simulation's by running enough trials
+struct TargetReference {
+    win: Estimate,
+    tie: Estimate,
+    equity: Estimate,
+    exact: bool,
+    samples: u64,
+}
+
+const REFERENCE_SE_RATIO: f64 = 10.0;
+const MAX_REFERENCE_TRIALS: u64 = 20_000_000;
+
+// trials that bring the reference's standard error down to a tenth of the simulation's:
+// the simulation sees the target in about n * (combos / 1326) hands, or as many as a precision run needs
+fn reference_trials(target: &Range, n_sims: u32, precision: Option<f64>) -> u64 {
+    let target_deals = n_sims as f64 * target.len() as f64 / 1326.0;
+    let mut trials = REFERENCE_SE_RATIO.powi(2) * target_deals;
+    if let Some(precision) = precision {
+        // a proportion's standard error is at most 0.5 / sqrt(n)
+        trials = trials.max((0.5 * Z_95 * REFERENCE_SE_RATIO / precision).powi(2));
+    }
+    (trials as u64).clamp(MONTE_CARLO_TRIALS, MAX_REFERENCE_TRIALS)
+}
+
+fn target_reference(target: &Range, opponents: usize, trials: u64, threads: usize, rng: &mut StdRng) -> Option<TargetReference> {
+    let mut ranges = vec![target.clone()];
+    ranges.extend((0..opponents).map(|_| Range::full()));
+    let run = |rng: &mut StdRng| range_equity_with(&ranges, &[], &[], Variant::Holdem, rng);
+    let first = match run(rng) {
+        Ok(result) => result,
+        Err(err) => {
+            eprintln!("no reference equity for the target: {}", err);
+            return None;
+        }
+    };
+    let exact = first.exact;
+    let mut results: Vec<EquityResult> = vec![first];
+
+    // every further batch of calculator trials on its own worker rng, like the simulation itself
+    if !exact {
+        let batches = trials.div_ceil(MONTE_CARLO_TRIALS).saturating_sub(1);
+        let threads = threads as u64;
+        let jobs: Vec<(u64, u64)> = (0..threads)
+            .map(|i| (batches / threads + u64::from(i < batches % threads), rng.gen()))
+            .collect();
+        let run = &run;
+        thread::scope(|scope| {
+            let workers: Vec<_> = jobs
+                .iter()
+                .map(|(n, seed)| {
+                    scope.spawn(move || {
+                        let mut rng = StdRng::seed_from_u64(*seed);
+                        (0..*n).filter_map(|_| run(&mut rng).ok()).collect::<Vec<EquityResult>>()
+                    })
+                })
+                .collect();
+            for w in workers {
+                results.extend(w.join().expect("reference worker panicked"));
+            }
+        });
+    }
+
+    // every batch pooled by the trials it ran
+    let samples: u64 = results.iter().map(|r| r.samples).sum();
+    let pooled = |figure: fn(&PlayerEquity) -> f64| {
+        results.iter().map(|r| figure(&r.players[0]) * r.samples as f64).sum::<f64>() / samples as f64
+    };
+    let estimate = |value: f64| {
+        if exact {
+            Estimate { value, std_error: 0.0 }
+        } else {
+            // equity per trial lies in [0, 1], so p(1 - p) bounds its variance as well
+            Estimate::proportion(value * samples as f64, samples)
+        }
+    };
+    Some(TargetReference {
+        win: estimate(pooled(|p| p.win)),
+        tie: estimate(pooled(|p| p.tie)),
+        equity: estimate(pooled(|p| p.equity)),
+        exact,
+        samples,
+    })
+}
+
+// everything one worker counts, merged once every worker is done
+#[derive(Default, Clone)]
+struct SimulationTally {
+    pairs: MonteCarloPairDistribution,
+    categories: MonteCarloCategoryDistribution,
+    seats: MonteCarloSeatResults,
+    target: MonteCarloTargetTracker,
+}
+impl SimulationTally {
+    fn new(n_sims: u32, players: usize, target: Option<&Range>) -> Self {
+        Self {
+            pairs: MonteCarloPairDistribution {
+                total_hands: n_sims as u64 * players as u64,
+                ..Default::default()
+            },
+            categories: MonteCarloCategoryDistribution {
+                total_hands: n_sims as u64,
+                seats: vec![[0; 9]; players],
+            },
+            seats: MonteCarloSeatResults {
+                total_hands: n_sims as u64,
+                wins: vec![0; players],
+                ties: vec![0; players],
+                equity: vec![0.0; players],
+                equity_sq: vec![0.0; players],
+            },
+            target: MonteCarloTargetTracker {
+                total_hands: n_sims as u64,
+                opponents: players - 1,
+                dealt_expected: target.map_or(0.0, |t| t.len() as f64 / 1326.0),
+                ..Default::default()
+            },
+        }
+    }
+
+    fn merge(&mut self, other: &SimulationTally) {
+        self.pairs.merge(&other.pairs);
+        self.categories.merge(&other.categories);
+        self.seats.merge(&other.seats);
+        self.target.merge(&other.target);
+    }
+
+    // every tracked quantity is within the precision at 95% confidence
+    // an empty target can never be dealt, so it doesn't hold the run up
+    fn converged(&self, precision: f64) -> bool {
+        let target = if self.target.dealt_expected > 0.0 { self.target.estimates().to_vec() } else { Vec::new() };
+        self.pairs
+            .estimates()
+            .into_iter()
+            .chain(self.categories.estimates())
+            .chain(self.seats.estimates())
+            .chain(target)
+            .all(|e| e.converged(precision))
+    }
+}
+
+// one worker's share of the hands, on its own rng stream
+fn deal_hands(n_sims: u32, seed: u64, target: Option<&Range>, players: usize) -> SimulationTally {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut tally = SimulationTally::new(n_sims, players, target);
+    let mut deck = Deck::new();
+    let mut holes: Vec<CardSet> = vec![CardSet::EMPTY; players];
+    let mut ranks: Vec<HandRank> = Vec::with_capacity(players);
 
     for _ in 0..n_sims {
-        let rng_hand = deck.deal(2).map(|cards| Hand::new(cards));
-        // error handler to use a new deck
-        if deck.cards.is_empty() {
-            deck = Box::new(Deck::new());
-            deck.shuffle();
-        }
-        match rng_hand {
-            Some(Ok(sim_hand)) => {
-                pair_tracker = track_simulation_pairs(pair_tracker, sim_hand);
+        // every hand is a real deal from a fresh deck: one card round the table twice,
+        // then burn and flop, burn and turn, burn and river
+        deck.reset();
+        deck.shuffle_with(&mut rng);
+        // cards come straight off the top of the deck, nothing is allocated per hand
+        holes.fill(CardSet::EMPTY);
+        for _ in 0..2 {
+            for hole in holes.iter_mut() {
+                if let Some(c) = deck.cards.pop() {
+                    hole.add(c);
+                }
             }
-            _ => println!("No hand dealt"),
         }
-    } // END FOR LOOP
+        let mut board = CardSet::EMPTY;
+        for street in [3, 1, 1] {
+            deck.cards.pop();
+            board = board | deck.deal_set(street).unwrap_or_default();
+        }
 
+        ranks.clear();
+        for (seat, hole) in holes.iter().enumerate() {
+            tally.pairs = track_simulation_pairs(tally.pairs, *hole);
+            if let Ok(rank) = evaluate_set(*hole | board) {
+                tally.categories.seats[seat][rank.category as usize] += 1;
+                ranks.push(rank);
+            }
+        }
+        tally.seats.record(&ranks);
+        if target.is_some_and(|t| t.contains(holes[0])) {
+            tally.target.record(&ranks);
+        }
+    } // END FOR LOOP
+    tally
+}
 
-    
-    //RETURN PAIR TRACKER STRUCT
-    println!("randomized hands dealt: \n {}", n_sims);
-    println!("pair distribution \n {}", &pair_tracker);
+// deals n hands split across the worker threads and merges what they counted
+fn run_batch(n_sims: u32, rng: &mut StdRng, threads: usize, target: Option<&Range>, players: usize) -> SimulationTally {
+    // split the hands as evenly as possible, the first workers take the remainder
+    let per_thread = n_sims / threads as u32;
+    let remainder = n_sims % threads as u32;
+    let jobs: Vec<(u32, u64)> = (0..threads as u32)
+        .map(|i| (per_thread + u32::from(i < remainder), rng.gen()))
+        .collect();
 
+    let tallies: Vec<SimulationTally> = thread::scope(|scope| {
+        let workers: Vec<_> = jobs
+            .iter()
+            .map(|(hands, seed)| scope.spawn(move || deal_hands(*hands, *seed, target, players)))
+            .collect();
+        workers.into_iter().map(|w| w.join().expect("simulation worker panicked")).collect()
+    });
+    // merged in worker order so the totals never depend on which thread finished first
+    let mut tally = SimulationTally::new(0, players, target);
+    for t in &tallies {
+        tally.merge(t);
+    }
+    tally
+}
 
+fn simulation_builder(
+    n_sims: u32,
+    rng: &mut StdRng,
+    threads: usize,
+    target: Option<&Range>,
+    players: usize,
+    reference: Option<&TargetReference>,
+    precision: Option<f64>,
+) {
+    let mut tally = run_batch(n_sims, rng, threads, target, players);
+    // with a precision the batches keep coming until every interval is narrow enough
+    if let Some(precision) = precision {
+        let mut batches = 1;
+        while !tally.converged(precision) && batches < MAX_BATCHES {
+            tally.merge(&run_batch(n_sims, rng, threads, target, players));
+            batches += 1;
+        }
+        if tally.converged(precision) {
+            println!(
+                "converged to ±{:.4}% at 95% confidence after {} hands",
+                100.0 * precision,
+                tally.seats.total_hands
+            );
+        } else {
+            println!(
+                "stopped after {} batches and {} hands without reaching ±{:.4}% at 95% confidence",
+                batches,
+                tally.seats.total_hands,
+                100.0 * precision
+            );
+        }
+    }
 
+    println!(
+        "randomized hands dealt: \n {} hands to {} players on {} threads",
+        tally.seats.total_hands, players, threads
+    );
+    println!("pair distribution \n{}", &tally.pairs);
+    println!("seven card hand distribution \n{}", &tally.categories);
+    println!("showdown results \n{}", &tally.seats);
+    if target.is_some() {
+        tally.target.print(reference);
+    }
 }
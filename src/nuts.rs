@@ -0,0 +1,129 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::cardset::CardSet;
+use crate::combos::hole_combos_with;
+use crate::eval::{evaluate_set_with, HandRank};
+use crate::starting_hand::StartingHand;
+use crate::{Card, HandError, Variant};
+
+// the nut ladder for a board: every two card holding grouped by the exact strength it makes,
+// strongest first. combos that tie (the board plays, or the suits don't matter) share a rung
+
+/// one rung of the ladder, every combo in it makes exactly the same hand strength
+#[derive(Debug, Clone, PartialEq)]
+pub struct NutHolding {
+    pub rank: HandRank,
+    pub combos: Vec<CardSet>,
+}
+
+impl NutHolding {
+    pub fn combo_count(&self) -> usize {
+        self.combos.len()
+    }
+
+    pub fn description(&self) -> String {
+        self.rank.description()
+    }
+
+    /// the starting hand classes in this rung with how many of their combos are live, highest cards first
+    pub fn classes(&self) -> Vec<(StartingHand, usize)> {
+        let mut counts: BTreeMap<StartingHand, usize> = BTreeMap::new();
+        for combo in &self.combos {
+            let cards: Vec<Card> = combo.iter().collect();
+            *counts.entry(StartingHand::from_cards(cards[0], cards[1])).or_insert(0) += 1;
+        }
+        counts.into_iter().rev().collect()
+    }
+}
+
+impl fmt::Display for NutHolding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // a class with a single live combo is shown as the exact cards
+        let classes: Vec<String> = self
+            .classes()
+            .iter()
+            .map(|(hand, live)| match self.combos.iter().find(|c| *live == 1 && is_class(**c, *hand)) {
+                Some(combo) => {
+                    let mut cards: Vec<Card> = combo.iter().collect();
+                    cards.sort_by(|a, b| b.cmp(a));
+                    cards.iter().map(|c| c.as_string()).collect()
+                }
+                None => hand.to_string(),
+            })
+            .collect();
+        write!(f, "{}: {} ({} combos)", self.description(), classes.join(", "), self.combo_count())
+    }
+}
+
+fn is_class(combo: CardSet, hand: StartingHand) -> bool {
+    let cards: Vec<Card> = combo.iter().collect();
+    hand.contains(cards[0], cards[1])
+}
+
+pub fn nut_hands(board: &[Card], top: usize, dead: &[Card]) -> Result<Vec<NutHolding>, HandError> {
+    nut_hands_with(board, top, dead, Variant::Holdem)
+}
+
+/// the top holdings on a 3 to 5 card board, the nuts first
+/// dead cards, such as the player's own hole cards, are left out of every combo count
+pub fn nut_hands_with(board: &[Card], top: usize, dead: &[Card], variant: Variant) -> Result<Vec<NutHolding>, HandError> {
+    let board_set = CardSet::from(board);
+    if board_set.len() != board.len() || board.len() < 3 || board.len() > 5 {
+        return Err(HandError::InvalidSize(board.len()));
+    }
+    let known: Vec<Card> = board.iter().chain(dead).copied().collect();
+
+    let mut ladder: BTreeMap<HandRank, Vec<CardSet>> = BTreeMap::new();
+    for combo in hole_combos_with(&known, variant) {
+        let rank = evaluate_set_with(combo | board_set, variant)?;
+        ladder.entry(rank).or_default().push(combo);
+    }
+    Ok(ladder
+        .into_iter()
+        .rev()
+        .take(top)
+        .map(|(rank, combos)| NutHolding { rank, combos })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eval::HandCategory;
+    use crate::Hand;
+
+    fn cards(s: &str) -> Vec<Card> {
+        s.parse::<Hand>().unwrap().cards
+    }
+
+    #[test]
+    fn queen_jack_of_hearts_is_the_nut_flush() {
+        let ladder = nut_hands(&cards("AhKh7h2c"), 3, &[]).unwrap();
+        assert_eq!(ladder.len(), 3);
+        assert_eq!(ladder[0].rank.category, HandCategory::Flush);
+        assert_eq!(ladder[0].combo_count(), 1);
+        assert_eq!(ladder[0].to_string(), "Flush, Ace-King-Queen-Jack-Seven high: QhJh (1 combos)");
+        assert!(ladder[0].rank > ladder[1].rank && ladder[1].rank > ladder[2].rank);
+    }
+
+    #[test]
+    fn dead_cards_drop_the_rungs_that_need_them() {
+        let board = cards("AhKh7h2c");
+        let live = nut_hands(&board, usize::MAX, &[]).unwrap();
+        let blocked = nut_hands(&board, usize::MAX, &cards("Qh")).unwrap();
+        let queen: Card = "Qh".parse().unwrap();
+        assert!(blocked.iter().all(|h| h.combos.iter().all(|c| !c.contains(queen))));
+        // every Qh flush is gone, JhTh is the nuts now
+        assert_eq!(blocked[0].to_string(), "Flush, Ace-King-Jack-Ten-Seven high: JhTh (1 combos)");
+        let expected: Vec<NutHolding> = live
+            .iter()
+            .map(|h| NutHolding { rank: h.rank, combos: h.combos.iter().copied().filter(|c| !c.contains(queen)).collect() })
+            .filter(|h| !h.combos.is_empty())
+            .collect();
+        assert_eq!(blocked, expected);
+        let count = |ladder: &[NutHolding]| ladder.iter().map(|h| h.combo_count()).sum::<usize>();
+        assert_eq!(count(&live), 48 * 47 / 2);
+        assert_eq!(count(&blocked), 47 * 46 / 2);
+    }
+}
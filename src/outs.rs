@@ -0,0 +1,239 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::cardset::CardSet;
+use crate::eval::{evaluate_set_with, HandCategory};
+use crate::texture::{board_texture_with, straight_windows};
+use crate::{Card, Deck, HandError, Suit, Variant};
+
+// outs and named draws for a player on the flop or turn
+// an out is an unseen card that lifts the player's hand to a better category using their hole cards,
+// it is dirty when the same card also brings in a flush or straight for someone else
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Draw {
+    FlushDraw,
+    // four in a row with both ends open
+    OpenEnded,
+    DoubleGutshot,
+    Gutshot,
+    // two running cards needed, flop only
+    BackdoorFlush,
+    BackdoorStraight,
+}
+
+impl Draw {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Draw::FlushDraw => "Flush draw",
+            Draw::OpenEnded => "Open-ended straight draw",
+            Draw::DoubleGutshot => "Double gutshot",
+            Draw::Gutshot => "Gutshot",
+            Draw::BackdoorFlush => "Backdoor flush draw",
+            Draw::BackdoorStraight => "Backdoor straight draw",
+        }
+    }
+}
+
+impl fmt::Display for Draw {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Out {
+    pub card: Card,
+    pub makes: HandCategory,
+    pub dirty: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OutsReport {
+    pub current: HandCategory,
+    pub outs: Vec<Out>,
+    pub draws: Vec<Draw>,
+}
+
+impl OutsReport {
+    pub fn count(&self) -> usize {
+        self.outs.len()
+    }
+
+    pub fn clean_count(&self) -> usize {
+        self.outs.iter().filter(|o| !o.dirty).count()
+    }
+
+    // outs grouped by what they make, in the order they were first found
+    pub fn by_category(&self, category: HandCategory) -> impl Iterator<Item = &Out> + '_ {
+        self.outs.iter().filter(move |o| o.makes == category)
+    }
+}
+
+pub fn outs(hole: &[Card], board: &[Card]) -> Result<OutsReport, HandError> {
+    outs_with(hole, board, Variant::Holdem)
+}
+
+/// outs and draws for two hole cards on a 3 or 4 card board
+pub fn outs_with(hole: &[Card], board: &[Card], variant: Variant) -> Result<OutsReport, HandError> {
+    let (hole_set, board_set) = (CardSet::from(hole), CardSet::from(board));
+    if hole_set.len() != 2 || hole.len() != 2 {
+        return Err(HandError::InvalidSize(hole.len()));
+    }
+    if board_set.len() != board.len() || !(3..=4).contains(&board.len()) || !hole_set.is_disjoint(&board_set) {
+        return Err(HandError::InvalidSize(board.len()));
+    }
+    let current = evaluate_set_with(hole_set | board_set, variant)?.category;
+    let strength = |c: HandCategory| c.strength(variant);
+    let before = board_texture_with(board, variant)?;
+
+    let mut found: Vec<Out> = Vec::new();
+    for card in Deck::for_variant(variant).cards {
+        if hole_set.contains(card) || board_set.contains(card) {
+            continue;
+        }
+        let next_board = board_set | CardSet::from(card);
+        let makes = evaluate_set_with(hole_set | next_board, variant)?.category;
+        // the improvement has to come from the hole cards, not from the board alone
+        if strength(makes) <= strength(current) || strength(makes) <= strength(board_category(next_board, variant)) {
+            continue;
+        }
+
+        let mut next: Vec<Card> = board.to_vec();
+        next.push(card);
+        let after = board_texture_with(&next, variant)?;
+        let new_flush = after.flush_suits.len() > before.flush_suits.len();
+        let new_straight = after.straights.len() > before.straights.len();
+        let dirty = (new_flush && strength(makes) < strength(HandCategory::Flush))
+            || (new_straight && strength(makes) < strength(HandCategory::Straight));
+        found.push(Out { card, makes, dirty });
+    }
+
+    Ok(OutsReport {
+        current,
+        outs: found,
+        draws: draws(hole_set, board_set, variant),
+    })
+}
+
+// category of the board cards by themselves, four card boards can only pair up
+fn board_category(board: CardSet, variant: Variant) -> HandCategory {
+    if let Ok(rank) = evaluate_set_with(board, variant) {
+        return rank.category;
+    }
+    let mut counts = [0u8; 13];
+    for c in board {
+        counts[(c.rank.high_value() - 2) as usize] += 1;
+    }
+    let pairs = counts.iter().filter(|n| **n == 2).count();
+    match counts.iter().max().copied().unwrap_or(0) {
+        4 => HandCategory::FourOfAKind,
+        3 => HandCategory::ThreeOfAKind,
+        2 if pairs == 2 => HandCategory::TwoPair,
+        2 => HandCategory::OnePair,
+        _ => HandCategory::HighCard,
+    }
+}
+
+fn draws(hole: CardSet, board: CardSet, variant: Variant) -> Vec<Draw> {
+    let all = hole | board;
+    let mut found: Vec<Draw> = Vec::new();
+
+    // flushes, a hole card has to be in the suit
+    let suited = |n: u32| {
+        Suit::ALL
+            .iter()
+            .any(|s| all.suit_mask(s).count_ones() == n && hole.suit_mask(s) != 0)
+    };
+    let made_flush = Suit::ALL.iter().any(|s| all.suit_mask(s).count_ones() >= 5);
+    if !made_flush && suited(4) {
+        found.push(Draw::FlushDraw);
+    } else if !made_flush && board.len() == 3 && suited(3) {
+        found.push(Draw::BackdoorFlush);
+    }
+
+    // straights, counted by the ranks that would complete one with a hole card in it
+    let (mask, hole_mask) = (all.rank_mask(), hole.rank_mask());
+    let windows = straight_windows(variant);
+    let made = |m: u16| windows.iter().any(|(_, w)| m & w == *w && hole_mask & w != 0);
+    if made(mask) {
+        return found;
+    }
+    let completing: Vec<u8> = (0..13u8).filter(|r| mask & (1 << r) == 0 && made(mask | 1 << r)).collect();
+    match completing.len() {
+        0 => {
+            let backdoor = windows
+                .iter()
+                .any(|(_, w)| (mask & w).count_ones() == 3 && hole_mask & w != 0);
+            if board.len() == 3 && backdoor {
+                found.push(Draw::BackdoorStraight);
+            }
+        }
+        1 => found.push(Draw::Gutshot),
+        _ => {
+            // four ranks in a row inside one window means both ends are live
+            let in_a_row = windows.iter().any(|(_, w)| {
+                let held = mask & w;
+                held.count_ones() == 4 && (held == w & (w << 1) || held == w & (w >> 1))
+            });
+            found.push(if in_a_row { Draw::OpenEnded } else { Draw::DoubleGutshot });
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Hand;
+
+    fn report(hole: &str, board: &str) -> OutsReport {
+        let cards = |s: &str| s.parse::<Hand>().unwrap().cards;
+        outs(&cards(hole), &cards(board)).unwrap()
+    }
+
+    #[test]
+    fn nut_flush_draw_with_overcards() {
+        let r = report("AhKh", "Qh7h2c");
+        assert_eq!(r.current, HandCategory::HighCard);
+        // nine hearts plus three aces and three kings
+        assert_eq!(r.count(), 15);
+        assert_eq!(r.by_category(HandCategory::Flush).count(), 9);
+        assert_eq!(r.by_category(HandCategory::OnePair).count(), 6);
+        assert_eq!(r.draws, vec![Draw::FlushDraw, Draw::BackdoorStraight]);
+    }
+
+    #[test]
+    fn open_ender_with_dirty_pair_outs() {
+        let r = report("9c8d", "7h6s2c");
+        assert_eq!(r.draws, vec![Draw::OpenEnded]);
+        assert_eq!(r.by_category(HandCategory::Straight).count(), 8);
+        let pairs: Vec<&Out> = r.by_category(HandCategory::OnePair).collect();
+        assert_eq!(pairs.len(), 6);
+        // a nine or an eight also puts four to a straight on the board
+        assert!(pairs.iter().all(|o| o.dirty));
+        assert!(r.by_category(HandCategory::Straight).all(|o| !o.dirty));
+    }
+
+    #[test]
+    fn gutshot() {
+        let r = report("9c7d", "Jh8s2c");
+        assert!(r.draws.contains(&Draw::Gutshot));
+        assert_eq!(r.by_category(HandCategory::Straight).count(), 4);
+    }
+
+    #[test]
+    fn made_hands_have_no_draws() {
+        let r = report("9c8d", "7h6sTc");
+        assert_eq!(r.current, HandCategory::Straight);
+        assert!(r.draws.is_empty());
+    }
+
+    #[test]
+    fn board_improvements_are_not_outs() {
+        // pairing the board lifts everyone to a pair, it doesn't count for the hole cards
+        let r = report("AhKd", "9c7s2d");
+        assert!(r.outs.iter().all(|o| o.card.rank.high_value() >= 13));
+    }
+}
@@ -0,0 +1,166 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::{Card, Hand, Rank, Suit};
+
+// text parsing for cards and hands, the inverse of Card::as_string and Hand's Display
+// "Ah", "AhKd" and "Th 9h 8c" are all accepted, whitespace between cards is optional
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CardParseError {
+    BadRank(char),
+    BadSuit(char),
+    // wrong number of characters for a rank, suit or card
+    InvalidLength(usize),
+    DuplicateCard(Card),
+    TooManyCards(usize),
+    // starting hand classes need two ranks plus s or o, pairs take no suffix
+    InvalidHandClass(String),
+    // a range token that isn't a class, a combo, a plus or a dash range
+    InvalidRange(String),
+    InvalidWeight(String),
+}
+
+impl fmt::Display for CardParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CardParseError::BadRank(c) => write!(f, "invalid rank character '{}'", c),
+            CardParseError::BadSuit(c) => write!(f, "invalid suit character '{}'", c),
+            CardParseError::InvalidLength(n) => write!(f, "unexpected input length {}", n),
+            CardParseError::DuplicateCard(c) => write!(f, "duplicate card {}", c.as_string()),
+            CardParseError::TooManyCards(n) => write!(f, "{} cards is more than a hand can hold", n),
+            CardParseError::InvalidHandClass(s) => write!(f, "invalid starting hand class '{}'", s),
+            CardParseError::InvalidRange(s) => write!(f, "invalid range token '{}'", s),
+            CardParseError::InvalidWeight(s) => write!(f, "invalid range weight '{}'", s),
+        }
+    }
+}
+
+impl Error for CardParseError {}
+
+pub(crate) fn parse_rank(c: char) -> Result<&'static Rank, CardParseError> {
+    match c.to_ascii_uppercase() {
+        '2' => Ok(&Rank::Two),
+        '3' => Ok(&Rank::Three),
+        '4' => Ok(&Rank::Four),
+        '5' => Ok(&Rank::Five),
+        '6' => Ok(&Rank::Six),
+        '7' => Ok(&Rank::Seven),
+        '8' => Ok(&Rank::Eight),
+        '9' => Ok(&Rank::Nine),
+        'T' => Ok(&Rank::Ten),
+        'J' => Ok(&Rank::Jack),
+        'Q' => Ok(&Rank::Queen),
+        'K' => Ok(&Rank::King),
+        'A' => Ok(&Rank::Ace),
+        _ => Err(CardParseError::BadRank(c)),
+    }
+}
+
+fn parse_suit(c: char) -> Result<&'static Suit, CardParseError> {
+    match c.to_ascii_lowercase() {
+        'h' => Ok(&Suit::Hearts),
+        'd' => Ok(&Suit::Diamonds),
+        'c' => Ok(&Suit::Clubs),
+        's' => Ok(&Suit::Spades),
+        _ => Err(CardParseError::BadSuit(c)),
+    }
+}
+
+fn single_char(s: &str) -> Result<char, CardParseError> {
+    let mut chars = s.trim().chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Ok(c),
+        _ => Err(CardParseError::InvalidLength(s.trim().chars().count())),
+    }
+}
+
+impl FromStr for Rank {
+    type Err = CardParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_rank(single_char(s)?).copied()
+    }
+}
+
+impl FromStr for Suit {
+    type Err = CardParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_suit(single_char(s)?).copied()
+    }
+}
+
+impl FromStr for Card {
+    type Err = CardParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let chars: Vec<char> = s.trim().chars().collect();
+        if chars.len() != 2 {
+            return Err(CardParseError::InvalidLength(chars.len()));
+        }
+        Ok(Card::new(parse_rank(chars[0])?, parse_suit(chars[1])?))
+    }
+}
+
+impl FromStr for Hand {
+    type Err = CardParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let chars: Vec<char> = s.chars().filter(|c| !c.is_whitespace()).collect();
+        if !chars.len().is_multiple_of(2) {
+            return Err(CardParseError::InvalidLength(chars.len()));
+        }
+        if chars.len() / 2 > 7 {
+            return Err(CardParseError::TooManyCards(chars.len() / 2));
+        }
+
+        let mut cards: Vec<Card> = Vec::with_capacity(chars.len() / 2);
+        for pair in chars.chunks(2) {
+            let card = Card::new(parse_rank(pair[0])?, parse_suit(pair[1])?);
+            if cards.contains(&card) {
+                return Err(CardParseError::DuplicateCard(card));
+            }
+            cards.push(card);
+        }
+        Hand::new(cards).map_err(|err| match err {
+            crate::HandError::InvalidSize(n) => CardParseError::TooManyCards(n),
+            // duplicates are caught above and Hand::new only checks the size
+            other => unreachable!("Hand::new failed with {}", other),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::starting_hand::StartingHand;
+
+    #[test]
+    fn cards_and_hands_round_trip() {
+        let card: Card = " Th ".parse().unwrap();
+        assert_eq!(card.as_string(), "Th");
+        let hand: Hand = "Th 9h8c".parse().unwrap();
+        let text: Vec<String> = hand.cards.iter().map(|c| c.as_string()).collect();
+        assert_eq!(text, ["Th", "9h", "8c"]);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!("Xh".parse::<Card>(), Err(CardParseError::BadRank('X')));
+        assert_eq!("Ax".parse::<Card>(), Err(CardParseError::BadSuit('x')));
+        assert_eq!("Ahh".parse::<Card>(), Err(CardParseError::InvalidLength(3)));
+        assert_eq!("".parse::<Rank>(), Err(CardParseError::InvalidLength(0)));
+        assert_eq!("AhK".parse::<Hand>().map(|h| h.cards), Err(CardParseError::InvalidLength(3)));
+        assert_eq!(
+            "AhKdAh".parse::<Hand>().map(|h| h.cards),
+            Err(CardParseError::DuplicateCard("Ah".parse().unwrap()))
+        );
+        assert_eq!(
+            "2c3c4c5c6c7c8c9c".parse::<Hand>().map(|h| h.cards),
+            Err(CardParseError::TooManyCards(8))
+        );
+        assert!(matches!("AAs".parse::<StartingHand>(), Err(CardParseError::InvalidHandClass(_))));
+    }
+}
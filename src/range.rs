@@ -0,0 +1,395 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::cardset::CardSet;
+use crate::parse::{parse_rank, CardParseError};
+use crate::starting_hand::StartingHand;
+use crate::Card;
+
+// weighted hand ranges in the usual notation: "QQ+, AKs, A5s-A2s, KQo, 76s:0.5"
+//
+// tokens are comma separated, each one of
+//   AA, AKs, AKo, AK       a class, no suffix means suited and offsuit
+//   QQ+, A9s+, KT+         pairs up to aces, or the kicker up to one below the top card
+//   99-66, A5s-A2s         every class between the two ends
+//   AhKh                   a single combo
+// and any token can carry a weight between 0 and 1 after a colon
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Suitedness {
+    Suited,
+    Offsuit,
+    Any,
+}
+
+/// a set of two card combos, each with a weight in (0, 1]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Range {
+    combos: BTreeMap<CardSet, f64>,
+}
+
+impl Range {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// all 1326 combos at full weight
+    pub fn full() -> Self {
+        let mut range = Range::new();
+        for hand in StartingHand::all() {
+            range.add_class(hand, 1.0);
+        }
+        range
+    }
+
+    // a weight of 0 or less takes the combo out
+    pub fn add_combo(&mut self, combo: CardSet, weight: f64) {
+        if combo.len() != 2 {
+            return;
+        }
+        if weight > 0.0 {
+            self.combos.insert(combo, weight.min(1.0));
+        } else {
+            self.combos.remove(&combo);
+        }
+    }
+
+    pub fn add_class(&mut self, hand: StartingHand, weight: f64) {
+        for combo in hand.combos() {
+            self.add_combo(combo, weight);
+        }
+    }
+
+    pub fn weight(&self, combo: CardSet) -> f64 {
+        self.combos.get(&combo).copied().unwrap_or(0.0)
+    }
+
+    pub fn contains(&self, combo: CardSet) -> bool {
+        self.combos.contains_key(&combo)
+    }
+
+    /// combos with their weights, in a fixed order
+    pub fn combos(&self) -> impl Iterator<Item = (CardSet, f64)> + '_ {
+        self.combos.iter().map(|(c, w)| (*c, *w))
+    }
+
+    /// drops every combo that shares a card with the dead cards
+    pub fn remove_blocked(&mut self, dead: CardSet) {
+        self.combos.retain(|combo, _| combo.is_disjoint(&dead));
+    }
+
+    pub fn without_blocked(&self, dead: CardSet) -> Range {
+        let mut range = self.clone();
+        range.remove_blocked(dead);
+        range
+    }
+
+    // number of combos regardless of weight
+    pub fn len(&self) -> usize {
+        self.combos.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.combos.is_empty()
+    }
+
+    // combos counted by weight, "AA:0.5" counts as 3
+    pub fn weighted_count(&self) -> f64 {
+        self.combos.values().sum()
+    }
+
+    // a class's shared weight when all of its combos are in the range at the same weight
+    fn class_weight(&self, hand: StartingHand) -> Option<f64> {
+        let mut weights = hand.combos().map(|c| self.combos.get(&c).copied());
+        let first = weights.next()??;
+        weights.all(|w| w == Some(first)).then_some(first)
+    }
+
+    /// compact notation, whole classes are folded into plus and dash runs
+    /// and matching suited and offsuit runs share one token without a suffix
+    pub fn to_notation(&self) -> String {
+        let mut tokens: Vec<String> = Vec::new();
+        let mut covered: BTreeSet<CardSet> = BTreeSet::new();
+
+        // runs of whole classes at one weight: pairs, then suited and offsuit hands for each top card
+        let mut runs_of = |line: Vec<StartingHand>| {
+            let mut runs: Vec<(Vec<StartingHand>, f64)> = Vec::new();
+            let mut run: Vec<StartingHand> = Vec::new();
+            let mut run_weight = 0.0;
+            for hand in line {
+                let weight = self.class_weight(hand);
+                if weight.is_some() {
+                    covered.extend(hand.combos());
+                }
+                match weight {
+                    Some(w) if run.is_empty() || w == run_weight => {
+                        run_weight = w;
+                        run.push(hand);
+                    }
+                    found => {
+                        if !run.is_empty() {
+                            runs.push((std::mem::take(&mut run), run_weight));
+                        }
+                        if let Some(w) = found {
+                            run_weight = w;
+                            run.push(hand);
+                        }
+                    }
+                }
+            }
+            if !run.is_empty() {
+                runs.push((run, run_weight));
+            }
+            runs
+        };
+
+        for (run, weight) in runs_of((2..=14u8).rev().map(StartingHand::pair).collect()) {
+            tokens.push(run_token(&run, weight, true));
+        }
+        for high in (3..=14u8).rev() {
+            let line = |suited| (2..high).rev().filter_map(|low| StartingHand::new(high, low, suited)).collect();
+            let suited = runs_of(line(true));
+            let mut offsuit = runs_of(line(false));
+            for (run, weight) in suited {
+                let bounds = |r: &[StartingHand]| (r[0].low(), r[r.len() - 1].low());
+                match offsuit.iter().position(|(o, w)| *w == weight && bounds(o) == bounds(&run)) {
+                    Some(i) => {
+                        offsuit.remove(i);
+                        tokens.push(run_token(&run, weight, false));
+                    }
+                    None => tokens.push(run_token(&run, weight, true)),
+                }
+            }
+            for (run, weight) in offsuit {
+                tokens.push(run_token(&run, weight, true));
+            }
+        }
+
+        // whatever is left belongs to partly blocked or partly weighted classes
+        for (combo, weight) in self.combos() {
+            if !covered.contains(&combo) {
+                let mut cards: Vec<Card> = combo.iter().collect();
+                cards.sort_by(|a, b| b.cmp(a));
+                let token: String = cards.iter().map(|c| c.as_string()).collect();
+                tokens.push(with_weight(token, weight));
+            }
+        }
+        tokens.join(", ")
+    }
+}
+
+// "QQ+", "A5s-A2s" or "KT+" for a run of classes from one line, strongest first
+fn run_token(run: &[StartingHand], weight: f64, suffix: bool) -> String {
+    let (top, bottom) = (run[0], run[run.len() - 1]);
+    let name = |hand: StartingHand| {
+        let text = hand.to_string();
+        if suffix || hand.is_pair() { text } else { text[..2].to_string() }
+    };
+    let at_the_top = if top.is_pair() { top.high() == 14 } else { top.low() == top.high() - 1 };
+    let token = match run.len() {
+        1 => name(top),
+        _ if at_the_top => format!("{}+", name(bottom)),
+        _ => format!("{}-{}", name(top), name(bottom)),
+    };
+    with_weight(token, weight)
+}
+
+fn with_weight(token: String, weight: f64) -> String {
+    if weight >= 1.0 {
+        token
+    } else {
+        format!("{}:{}", token, weight)
+    }
+}
+
+impl fmt::Display for Range {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_notation())
+    }
+}
+
+// (higher rank value, lower rank value, suitedness) of a class token such as "A5s" or "77"
+fn parse_shape(token: &str) -> Result<(u8, u8, Suitedness), CardParseError> {
+    let chars: Vec<char> = token.chars().collect();
+    let invalid = || CardParseError::InvalidRange(token.to_string());
+    if chars.len() < 2 || chars.len() > 3 {
+        return Err(invalid());
+    }
+    let a = parse_rank(chars[0])?.high_value();
+    let b = parse_rank(chars[1])?.high_value();
+    let suitedness = match (a == b, chars.get(2).map(|c| c.to_ascii_lowercase())) {
+        (_, None) => Suitedness::Any,
+        (false, Some('s')) => Suitedness::Suited,
+        (false, Some('o')) => Suitedness::Offsuit,
+        _ => return Err(invalid()),
+    };
+    Ok((a.max(b), a.min(b), suitedness))
+}
+
+fn classes(high: u8, low: u8, suitedness: Suitedness) -> Vec<StartingHand> {
+    if high == low {
+        return vec![StartingHand::pair(high)];
+    }
+    let mut hands = Vec::new();
+    if suitedness != Suitedness::Offsuit {
+        hands.extend(StartingHand::new(high, low, true));
+    }
+    if suitedness != Suitedness::Suited {
+        hands.extend(StartingHand::new(high, low, false));
+    }
+    hands
+}
+
+fn parse_combo(token: &str) -> Option<CardSet> {
+    let chars: Vec<char> = token.chars().collect();
+    if chars.len() != 4 {
+        return None;
+    }
+    let first: Card = chars[..2].iter().collect::<String>().parse().ok()?;
+    let second: Card = chars[2..].iter().collect::<String>().parse().ok()?;
+    (first != second).then(|| CardSet::from(first) | CardSet::from(second))
+}
+
+impl FromStr for Range {
+    type Err = CardParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut range = Range::new();
+        for raw in s.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+            let token: String = raw.chars().filter(|c| !c.is_whitespace()).collect();
+            let (body, weight) = match token.split_once(':') {
+                Some((body, w)) => {
+                    let weight: f64 = w.parse().map_err(|_| CardParseError::InvalidWeight(w.to_string()))?;
+                    if !(0.0..=1.0).contains(&weight) {
+                        return Err(CardParseError::InvalidWeight(w.to_string()));
+                    }
+                    (body, weight)
+                }
+                None => (token.as_str(), 1.0),
+            };
+            let invalid = || CardParseError::InvalidRange(raw.to_string());
+
+            if let Some(combo) = parse_combo(body) {
+                range.add_combo(combo, weight);
+                continue;
+            }
+
+            let mut hands: Vec<StartingHand> = Vec::new();
+            if let Some(base) = body.strip_suffix('+') {
+                let (high, low, suitedness) = parse_shape(base)?;
+                if high == low {
+                    for r in high..=14 {
+                        hands.push(StartingHand::pair(r));
+                    }
+                } else {
+                    for l in low..high {
+                        hands.extend(classes(high, l, suitedness));
+                    }
+                }
+            } else if let Some((first, second)) = body.split_once('-') {
+                let (h1, l1, s1) = parse_shape(first)?;
+                let (h2, l2, s2) = parse_shape(second)?;
+                if h1 == l1 && h2 == l2 {
+                    for r in h1.min(h2)..=h1.max(h2) {
+                        hands.push(StartingHand::pair(r));
+                    }
+                } else if h1 == h2 && s1 == s2 && h1 != l1 && h2 != l2 {
+                    for l in l1.min(l2)..=l1.max(l2) {
+                        hands.extend(classes(h1, l, s1));
+                    }
+                } else {
+                    return Err(invalid());
+                }
+            } else {
+                let (high, low, suitedness) = parse_shape(body)?;
+                hands = classes(high, low, suitedness);
+            }
+
+            for hand in hands {
+                range.add_class(hand, weight);
+            }
+        }
+        Ok(range)
+    }
+}
+
+impl From<StartingHand> for Range {
+    fn from(hand: StartingHand) -> Self {
+        let mut range = Range::new();
+        range.add_class(hand, 1.0);
+        range
+    }
+}
+
+impl From<CardSet> for Range {
+    fn from(combo: CardSet) -> Self {
+        let mut range = Range::new();
+        range.add_combo(combo, 1.0);
+        range
+    }
+}
+
+// every combo at full weight
+impl FromIterator<CardSet> for Range {
+    fn from_iter<I: IntoIterator<Item = CardSet>>(iter: I) -> Self {
+        let mut range = Range::new();
+        for combo in iter {
+            range.add_combo(combo, 1.0);
+        }
+        range
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(s: &str) -> Range {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn notation_round_trips() {
+        let r = range("QQ+, AKs, A5s-A2s, KQo, 76s:0.5");
+        assert_eq!(r.len(), 18 + 4 + 16 + 12 + 4);
+        assert_eq!(r.weighted_count(), 52.0);
+        assert_eq!(r.to_notation(), "QQ+, AKs, A5s-A2s, KQo, 76s:0.5");
+        assert_eq!(range(&r.to_notation()), r);
+    }
+
+    #[test]
+    fn suited_and_offsuit_runs_merge() {
+        assert_eq!(range("AK").to_notation(), "AK");
+        assert_eq!(range("KT+").to_notation(), "KT+");
+        assert_eq!(range("A5-A2, 76s, 76o:0.5").to_notation(), "A5-A2, 76s, 76o:0.5");
+        assert_eq!(range("KTs+, KJo+").to_notation(), "KTs+, KJo+");
+        for notation in ["AK", "KT+", "A5-A2, 76s, 76o:0.5", "KTs+, KJo+"] {
+            assert_eq!(range(&range(notation).to_notation()), range(notation));
+        }
+    }
+
+    #[test]
+    fn blocked_classes_fall_back_to_single_combos() {
+        let dead = CardSet::from("Ah".parse::<Card>().unwrap());
+        let r = range("AA, KK").without_blocked(dead);
+        assert_eq!(r.len(), 9);
+        assert_eq!(r.to_notation(), "KK, AdAc, AsAc, AsAd");
+        assert_eq!(range(&r.to_notation()), r);
+    }
+
+    #[test]
+    fn plus_and_dash_ranges_expand() {
+        assert_eq!(range("KT+"), range("KQ, KJ, KT"));
+        assert_eq!(range("99-66"), range("66-99"));
+        assert_eq!(range("AhKh").len(), 1);
+        assert_eq!(range("AA:0").len(), 0);
+    }
+
+    #[test]
+    fn bad_tokens_are_rejected() {
+        assert!(matches!("AK-QJ".parse::<Range>(), Err(CardParseError::InvalidRange(_))));
+        assert!(matches!("AA:1.5".parse::<Range>(), Err(CardParseError::InvalidWeight(_))));
+        assert!(matches!("AKx".parse::<Range>(), Err(CardParseError::InvalidRange(_))));
+    }
+}
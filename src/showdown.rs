@@ -0,0 +1,213 @@
+use std::collections::HashMap;
+use std::error::Error;
+
+use serde::{Deserialize, Serialize};
+
+use crate::eval::BestHand;
+use crate::GameMaster;
+
+// showdown resolution: side pots from each player's contribution, winners by best five,
+// exact splits with odd chips going to the first winners left of the button
+
+/// a main or side pot and the seats that can still win it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Pot {
+    pub amount: i32,
+    pub eligible: Vec<usize>,
+}
+
+/// how one pot was paid out, shares line up with winners
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PotAward {
+    pub amount: i32,
+    pub winners: Vec<usize>,
+    pub shares: Vec<i32>,
+    // None when everyone else folded and no cards had to be shown
+    pub winning_hand: Option<BestHand>,
+}
+
+impl GameMaster {
+    // small blind sits left of the button, heads up the button posts the small blind
+    pub fn button_position(&self) -> usize {
+        let n = self.players.len().max(1);
+        if n == 2 {
+            (self.bb_position + 1) % n
+        } else {
+            (self.bb_position + n - 2) % n
+        }
+    }
+
+    /// splits everything contributed this hand into a main pot and side pots
+    /// chips from folded players stay in the pots but folded seats are never eligible,
+    /// live seats are expected to have matched the bet or be all in, see fold_unmatched
+    pub fn pots(&self) -> Vec<Pot> {
+        let mut levels: Vec<i32> = self.players.iter().map(|p| p.contributed).filter(|c| *c > 0).collect();
+        levels.sort_unstable();
+        levels.dedup();
+
+        let mut pots: Vec<Pot> = Vec::new();
+        let mut previous = 0;
+        for level in levels {
+            let amount: i32 = self
+                .players
+                .iter()
+                .map(|p| p.contributed.min(level) - p.contributed.min(previous))
+                .sum();
+            let eligible: Vec<usize> = self
+                .players
+                .iter()
+                .enumerate()
+                .filter(|(_, p)| !p.folded && p.contributed >= level)
+                .map(|(seat, _)| seat)
+                .collect();
+            previous = level;
+
+            // a layer nobody live can claim, or one with the same contenders, joins the last pot
+            match pots.last_mut() {
+                Some(last) if eligible.is_empty() || last.eligible == eligible => last.amount += amount,
+                _ => pots.push(Pot { amount, eligible }),
+            }
+        }
+        // the same goes for a bottom layer only folded players paid into
+        if pots.len() > 1 && pots[0].eligible.is_empty() {
+            let orphan = pots.remove(0);
+            pots[0].amount += orphan.amount;
+        }
+        pots
+    }
+
+    /// folds every live seat that put in less than the biggest contribution and still has chips behind,
+    /// for hands settled without a betting round: nobody called, so the short seats give up their claim
+    /// seats with no chips left are all in and keep their claim on the pots they paid into
+    pub fn fold_unmatched(&mut self) -> Vec<usize> {
+        let top = self.players.iter().filter(|p| !p.folded).map(|p| p.contributed).max().unwrap_or(0);
+        let mut folded: Vec<usize> = Vec::new();
+        for (seat, p) in self.players.iter_mut().enumerate() {
+            if !p.folded && p.contributed < top && p.chips > 0 {
+                p.fold();
+                folded.push(seat);
+            }
+        }
+        self.gamestate.players = Some(self.players.clone());
+        folded
+    }
+
+    /// compares every live player's best hand, pays out each pot and clears the contributions
+    pub fn showdown(&mut self) -> Result<Vec<PotAward>, Box<dyn Error>> {
+        let pots = self.pots();
+        let n = self.players.len();
+        let button = self.button_position();
+        // clockwise distance from the button, the first seat to its left comes first
+        let seat_order = |seat: usize| (seat + n - button - 1) % n;
+
+        let mut best: HashMap<usize, BestHand> = HashMap::new();
+        let mut awards: Vec<PotAward> = Vec::new();
+        for pot in pots {
+            let mut winners: Vec<usize> = Vec::new();
+            let mut winning_hand: Option<BestHand> = None;
+            if pot.eligible.len() == 1 {
+                winners = pot.eligible.clone();
+            } else {
+                for seat in &pot.eligible {
+                    if !best.contains_key(seat) {
+                        best.insert(*seat, self.gamestate.best_hand(&self.players[*seat])?);
+                    }
+                }
+                let top = pot.eligible.iter().filter_map(|s| best.get(s)).map(|b| b.rank).max();
+                for seat in &pot.eligible {
+                    if best.get(seat).map(|b| b.rank) == top {
+                        winners.push(*seat);
+                        winning_hand = best.get(seat).cloned();
+                    }
+                }
+            }
+            if winners.is_empty() {
+                continue;
+            }
+            winners.sort_by_key(|s| seat_order(*s));
+
+            let share = pot.amount / winners.len() as i32;
+            let odd_chips = (pot.amount % winners.len() as i32) as usize;
+            let shares: Vec<i32> = (0..winners.len())
+                .map(|i| if i < odd_chips { share + 1 } else { share })
+                .collect();
+            for (seat, chips) in winners.iter().zip(&shares) {
+                self.players[*seat].chips += chips;
+            }
+            awards.push(PotAward { amount: pot.amount, winners, shares, winning_hand });
+        }
+
+        for p in &mut self.players {
+            p.contributed = 0;
+        }
+        self.gamestate.players = Some(self.players.clone());
+        Ok(awards)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Game, Hand, Player};
+
+    // (starting stack, chips put in) for each seat
+    fn table(seats: &[(i32, i32)]) -> GameMaster {
+        let players: Vec<Player> = seats
+            .iter()
+            .enumerate()
+            .map(|(i, (stack, c))| {
+                let mut p = Player::new(format!("p{}", i));
+                p.chips = *stack;
+                p.commit_chips(*c);
+                p
+            })
+            .collect();
+        GameMaster::new(Game::new(Some(1)), players)
+    }
+
+    #[test]
+    fn unmatched_seats_fold_before_the_showdown() {
+        // blinds posted, nobody acted
+        let mut gm = table(&[(1000, 10), (1000, 20), (1000, 0)]);
+        assert_eq!(gm.fold_unmatched(), vec![0, 2]);
+        let awards = gm.showdown().unwrap();
+        assert_eq!(awards.len(), 1);
+        assert_eq!(awards[0].winners, vec![1]);
+        assert_eq!(awards[0].shares, vec![30]);
+        assert!(awards[0].winning_hand.is_none());
+    }
+
+    #[test]
+    fn side_pots_follow_the_all_in_levels() {
+        let mut gm = table(&[(50, 50), (1000, 100), (1000, 100), (1000, 30)]);
+        gm.players[3].fold();
+        assert_eq!(
+            gm.pots(),
+            vec![
+                Pot { amount: 180, eligible: vec![0, 1, 2] },
+                Pot { amount: 100, eligible: vec![1, 2] },
+            ]
+        );
+        // the all in seat keeps its claim
+        assert!(gm.fold_unmatched().is_empty());
+    }
+
+    #[test]
+    fn odd_chip_goes_left_of_the_button() {
+        let mut gm = table(&[(1000, 10), (1000, 10), (1000, 5)]);
+        gm.players[2].fold();
+        // three handed with the big blind in seat 2 puts the button on seat 0
+        gm.bb_position = 2;
+        assert_eq!(gm.button_position(), 0);
+        gm.players[0].hand = Some("2c3d".parse::<Hand>().unwrap());
+        gm.players[1].hand = Some("4c5d".parse::<Hand>().unwrap());
+        let board: Hand = "AsKsQsJsTs".parse().unwrap();
+        gm.gamestate.table_cards = Some(board.cards);
+
+        let awards = gm.showdown().unwrap();
+        assert_eq!(awards[0].amount, 25);
+        assert_eq!(awards[0].winners, vec![1, 0]);
+        assert_eq!(awards[0].shares, vec![13, 12]);
+        assert!(gm.players.iter().all(|p| p.contributed == 0));
+    }
+}
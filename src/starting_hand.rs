@@ -0,0 +1,190 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::cardset::CardSet;
+use crate::parse::{parse_rank, CardParseError};
+use crate::{Card, Rank, Suit};
+
+// the 169 strategically distinct starting hands: 13 pairs, 78 suited and 78 offsuit
+
+/// a starting hand class such as "AKs", "T9o" or "77"
+/// ranks are stored as values (2 = deuce .. 14 = ace) with the higher one first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct StartingHand {
+    high: u8,
+    low: u8,
+    suited: bool,
+}
+
+impl StartingHand {
+    pub fn from_cards(a: Card, b: Card) -> Self {
+        let (x, y) = (a.rank.high_value(), b.rank.high_value());
+        Self {
+            high: x.max(y),
+            low: x.min(y),
+            suited: x != y && a.suit == b.suit,
+        }
+    }
+
+    pub fn pair(rank: u8) -> Self {
+        Self { high: rank, low: rank, suited: false }
+    }
+
+    // two different rank values in either order, pairs can't be suited
+    pub fn new(a: u8, b: u8, suited: bool) -> Option<Self> {
+        let valid = |r: u8| (2..=14).contains(&r);
+        if !valid(a) || !valid(b) || (a == b && suited) {
+            return None;
+        }
+        Some(Self { high: a.max(b), low: a.min(b), suited })
+    }
+
+    /// every class, pairs from aces down, then suited and offsuit hands from AK down
+    pub fn all() -> impl Iterator<Item = StartingHand> {
+        let pairs = (2..=14u8).rev().map(StartingHand::pair);
+        let unpaired = (2..=14u8).rev().flat_map(|high| {
+            (2..high).rev().flat_map(move |low| {
+                [true, false].into_iter().map(move |suited| StartingHand { high, low, suited })
+            })
+        });
+        pairs.chain(unpaired)
+    }
+
+    pub fn high(&self) -> u8 {
+        self.high
+    }
+
+    pub fn low(&self) -> u8 {
+        self.low
+    }
+
+    pub fn is_pair(&self) -> bool {
+        self.high == self.low
+    }
+
+    pub fn is_suited(&self) -> bool {
+        self.suited
+    }
+
+    /// 6 for pairs, 4 for suited and 12 for offsuit hands
+    pub fn combo_count(&self) -> usize {
+        match (self.is_pair(), self.suited) {
+            (true, _) => 6,
+            (false, true) => 4,
+            (false, false) => 12,
+        }
+    }
+
+    /// the concrete two card hands that make up this class
+    pub fn combos(&self) -> impl Iterator<Item = CardSet> + '_ {
+        let high = &Rank::ALL[(self.high - 2) as usize];
+        let low = &Rank::ALL[(self.low - 2) as usize];
+        (0..4).flat_map(move |s1| {
+            (0..4)
+                .filter(move |s2| match (self.is_pair(), self.suited) {
+                    (true, _) => *s2 > s1,
+                    (false, true) => *s2 == s1,
+                    (false, false) => *s2 != s1,
+                })
+                .map(move |s2| CardSet::from(Card::new(high, &Suit::ALL[s1])) | CardSet::from(Card::new(low, &Suit::ALL[s2])))
+        })
+    }
+
+    pub fn contains(&self, a: Card, b: Card) -> bool {
+        StartingHand::from_cards(a, b) == *self
+    }
+}
+
+impl fmt::Display for StartingHand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let high = Rank::ALL[(self.high - 2) as usize].to_string();
+        let low = Rank::ALL[(self.low - 2) as usize].to_string();
+        match (self.is_pair(), self.suited) {
+            (true, _) => write!(f, "{}{}", high, low),
+            (false, true) => write!(f, "{}{}s", high, low),
+            (false, false) => write!(f, "{}{}o", high, low),
+        }
+    }
+}
+
+impl FromStr for StartingHand {
+    type Err = CardParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let chars: Vec<char> = s.trim().chars().collect();
+        let invalid = || CardParseError::InvalidHandClass(s.trim().to_string());
+        if chars.len() < 2 || chars.len() > 3 {
+            return Err(invalid());
+        }
+        let a = parse_rank(chars[0])?.high_value();
+        let b = parse_rank(chars[1])?.high_value();
+        let suited = match (a == b, chars.get(2).map(|c| c.to_ascii_lowercase())) {
+            (true, None) => false,
+            (false, Some('s')) => true,
+            (false, Some('o')) => false,
+            _ => return Err(invalid()),
+        };
+        StartingHand::new(a, b, suited).ok_or_else(invalid)
+    }
+}
+
+/// maps a (hole, board) spot onto a canonical suit labelling
+/// spots that only differ by a permutation of suits come out identical, so the returned
+/// sets can key an equity cache. suits are sorted by their board rank mask first and
+/// hole rank mask second, then relabelled clubs, diamonds, hearts, spades in that order
+pub fn canonical_suits(hole: CardSet, board: CardSet) -> (CardSet, CardSet) {
+    let mut order: [usize; 4] = [0, 1, 2, 3];
+    let key = |s: usize| (board.suit_mask(&Suit::ALL[s]), hole.suit_mask(&Suit::ALL[s]));
+    order.sort_by_key(|s| std::cmp::Reverse(key(*s)));
+
+    let (mut hole_bits, mut board_bits) = (0u64, 0u64);
+    for (new_suit, old_suit) in order.iter().enumerate() {
+        let (b, h) = key(*old_suit);
+        board_bits |= (b as u64) << (new_suit * 16);
+        hole_bits |= (h as u64) << (new_suit * 16);
+    }
+    (CardSet::from_bits(hole_bits), CardSet::from_bits(board_bits))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Hand;
+
+    fn set(s: &str) -> CardSet {
+        CardSet::from(s.parse::<Hand>().unwrap().cards.as_slice())
+    }
+
+    #[test]
+    fn classes_cover_every_combo_once() {
+        let all: Vec<StartingHand> = StartingHand::all().collect();
+        assert_eq!(all.len(), 169);
+        assert_eq!(all.iter().map(|h| h.combo_count()).sum::<usize>(), 1326);
+        let mut seen = std::collections::BTreeSet::new();
+        for hand in &all {
+            let combos: Vec<CardSet> = hand.combos().collect();
+            assert_eq!(combos.len(), hand.combo_count(), "{}", hand);
+            for combo in combos {
+                let cards: Vec<Card> = combo.iter().collect();
+                assert_eq!(StartingHand::from_cards(cards[0], cards[1]), *hand);
+                assert!(seen.insert(combo));
+            }
+        }
+    }
+
+    #[test]
+    fn suit_permutations_share_a_canonical_key() {
+        // hearts and spades swapped, clubs and diamonds swapped
+        let a = canonical_suits(set("AhKh"), set("Qh7s2c"));
+        let b = canonical_suits(set("AsKs"), set("Qs7h2d"));
+        assert_eq!(a, b);
+        assert_ne!(a, canonical_suits(set("AhKs"), set("Qh7s2c")));
+    }
+
+    #[test]
+    fn class_names_round_trip() {
+        for hand in StartingHand::all() {
+            assert_eq!(hand.to_string().parse::<StartingHand>().unwrap(), hand);
+        }
+    }
+}
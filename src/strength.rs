@@ -0,0 +1,216 @@
+use std::error::Error;
+
+use serde::{Deserialize, Serialize};
+
+use crate::cardset::CardSet;
+use crate::combos::board_completions_with;
+use crate::equity::EquityError;
+use crate::eval::evaluate_set_with;
+use crate::range::Range;
+use crate::{Card, GameMaster, Variant};
+
+// hand strength and hand potential as used by poker bots (Billings et al.)
+//   HS    share of opponent combos we beat right now, ties count half
+//   PPot  chance of ending up ahead when behind or tied now
+//   NPot  chance of ending up behind when ahead or tied now
+//   EHS   HS^n + (1 - HS^n) * PPot against n opponents
+// opponent combos are weighted by their range weight, potential looks all the way to the river
+
+const AHEAD: usize = 0;
+const TIED: usize = 1;
+const BEHIND: usize = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct HandStrength {
+    // against a single opponent
+    pub hs: f64,
+    // against every opponent at once, hs to the power of the opponent count
+    pub hs_n: f64,
+    pub ppot: f64,
+    pub npot: f64,
+    pub ehs: f64,
+    pub opponents: usize,
+}
+
+pub fn hand_strength(hole: &[Card], board: &[Card], range: &Range, opponents: usize) -> Result<HandStrength, EquityError> {
+    hand_strength_with(hole, board, range, opponents, Variant::Holdem)
+}
+
+/// strength of the hole cards on a 3 to 5 card board against opponents all holding the given range
+pub fn hand_strength_with(
+    hole: &[Card],
+    board: &[Card],
+    range: &Range,
+    opponents: usize,
+    variant: Variant,
+) -> Result<HandStrength, EquityError> {
+    let (hole_set, board_set) = (CardSet::from(hole), CardSet::from(board));
+    if hole.len() != 2 || hole_set.len() != 2 || !hole_set.is_disjoint(&board_set) {
+        return Err(EquityError::HoleCards(hole.len()));
+    }
+    if board_set.len() != board.len() || board.len() < 3 || board.len() > 5 {
+        return Err(EquityError::BoardSize(board.len()));
+    }
+    if opponents == 0 {
+        return Err(EquityError::PlayerCount(opponents + 1));
+    }
+    let known = hole_set | board_set;
+    let ours = evaluate_set_with(known, variant).map_err(|_| EquityError::BoardSize(board.len()))?;
+
+    let compare = |a, b| match a {
+        _ if a > b => AHEAD,
+        _ if a == b => TIED,
+        _ => BEHIND,
+    };
+    let mut now = [0.0f64; 3];
+    // potential[now][at the river], weighted by combo weight and counted per runout
+    let mut potential = [[0.0f64; 3]; 3];
+    let mut runouts = [0.0f64; 3];
+    for (combo, weight) in range.combos() {
+        if !combo.is_disjoint(&known) || combo.iter().any(|c| !variant.uses_rank(c.rank)) {
+            continue;
+        }
+        let theirs = match evaluate_set_with(combo | board_set, variant) {
+            Ok(rank) => rank,
+            Err(_) => continue,
+        };
+        let index = compare(ours, theirs);
+        now[index] += weight;
+
+        if board.len() == 5 {
+            continue;
+        }
+        let dead: Vec<Card> = (hole_set | combo).iter().collect();
+        for runout in board_completions_with(board, &dead, variant) {
+            if let (Ok(a), Ok(b)) = (
+                evaluate_set_with(hole_set | runout, variant),
+                evaluate_set_with(combo | runout, variant),
+            ) {
+                potential[index][compare(a, b)] += weight;
+                runouts[index] += weight;
+            }
+        }
+    }
+
+    let total: f64 = now.iter().sum();
+    if total == 0.0 {
+        return Err(EquityError::EmptyRange(1));
+    }
+    let hs = (now[AHEAD] + now[TIED] / 2.0) / total;
+    let ratio = |num: f64, den: f64| if den > 0.0 { num / den } else { 0.0 };
+    let ppot = ratio(
+        potential[BEHIND][AHEAD] + potential[BEHIND][TIED] / 2.0 + potential[TIED][AHEAD] / 2.0,
+        runouts[BEHIND] + runouts[TIED] / 2.0,
+    );
+    let npot = ratio(
+        potential[AHEAD][BEHIND] + potential[TIED][BEHIND] / 2.0 + potential[AHEAD][TIED] / 2.0,
+        runouts[AHEAD] + runouts[TIED] / 2.0,
+    );
+    let hs_n = hs.powi(opponents as i32);
+    Ok(HandStrength {
+        hs,
+        hs_n,
+        ppot,
+        npot,
+        ehs: hs_n + (1.0 - hs_n) * ppot,
+        opponents,
+    })
+}
+
+impl GameMaster {
+    /// sets hand_equity to each live player's EHS against the other live seats all holding the given range,
+    /// what a player can work out without seeing anyone's cards, unlike update_equity
+    /// does nothing before the flop
+    pub fn update_hand_strength(&mut self, opponents: &Range) -> Result<(), Box<dyn Error>> {
+        let board: Vec<Card> = self.gamestate.table_cards.clone().unwrap_or_default();
+        if board.len() < 3 {
+            return Ok(());
+        }
+        let live = self.players.iter().filter(|p| !p.folded && p.hand.is_some()).count();
+        for p in self.players.iter_mut().filter(|p| !p.folded) {
+            if let Some(h) = &p.hand {
+                let strength = hand_strength_with(&h.cards, &board, opponents, live.saturating_sub(1).max(1), self.gamestate.variant)?;
+                p.hand_equity = strength.ehs;
+            }
+        }
+        self.gamestate.players = Some(self.players.clone());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Game, Hand, Player};
+
+    fn cards(s: &str) -> Vec<Card> {
+        s.parse::<Hand>().unwrap().cards
+    }
+
+    #[test]
+    fn nuts_on_the_river() {
+        let s = hand_strength(&cards("AhKh"), &cards("QhJhTh2c3d"), &Range::full(), 3).unwrap();
+        assert_eq!(s.hs, 1.0);
+        assert_eq!(s.ppot, 0.0);
+        assert_eq!(s.npot, 0.0);
+        assert_eq!(s.ehs, 1.0);
+    }
+
+    #[test]
+    fn matches_the_published_example() {
+        // AdQc on 3h 4c Jh from Billings et al.: HS .585, PPot .208, NPot .274
+        let s = hand_strength(&cards("AdQc"), &cards("3h4cJh"), &Range::full(), 1).unwrap();
+        assert!((s.hs - 0.585).abs() < 0.001, "{}", s.hs);
+        assert!((s.ppot - 0.208).abs() < 0.001, "{}", s.ppot);
+        assert!((s.npot - 0.274).abs() < 0.001, "{}", s.npot);
+    }
+
+    #[test]
+    fn flush_draw_has_potential() {
+        // nine hearts and six overcard outs, about half the runouts come good when behind
+        let s = hand_strength(&cards("AhKh"), &cards("Qh7h2c"), &Range::full(), 1).unwrap();
+        assert!(s.ppot > 0.45 && s.ppot < 0.55, "{}", s.ppot);
+        assert!(s.ehs > s.hs);
+    }
+
+    #[test]
+    fn more_opponents_raise_the_power() {
+        let (hole, board) = (cards("AhQd"), cards("Ac7d9s2h3c"));
+        for n in 1..=5 {
+            let s = hand_strength(&hole, &board, &Range::full(), n).unwrap();
+            assert!((s.hs_n - s.hs.powi(n as i32)).abs() < 1e-12);
+            assert_eq!(s.opponents, n);
+        }
+    }
+
+    #[test]
+    fn errors() {
+        let full = Range::full();
+        assert_eq!(hand_strength(&cards("AhKhQh"), &cards("2c3c4c"), &full, 1), Err(EquityError::HoleCards(3)));
+        assert_eq!(hand_strength(&cards("AhKh"), &cards("Ah3c4c"), &full, 1), Err(EquityError::HoleCards(2)));
+        assert_eq!(hand_strength(&cards("AhKh"), &cards("2c3c"), &full, 1), Err(EquityError::BoardSize(2)));
+        assert_eq!(hand_strength(&cards("AhKh"), &cards("2c3c4c"), &full, 0), Err(EquityError::PlayerCount(1)));
+        assert_eq!(hand_strength(&cards("AhKh"), &cards("2c3c4c"), &"AhAs".parse().unwrap(), 1), Err(EquityError::EmptyRange(1)));
+    }
+
+    #[test]
+    fn update_hand_strength_sets_ehs_after_the_flop() {
+        let players: Vec<Player> = ["AhKh", "QsQd"]
+            .iter()
+            .map(|h| {
+                let mut p = Player::new(h.to_string());
+                p.hand = Some(h.parse().unwrap());
+                p
+            })
+            .collect();
+        let mut gm = GameMaster::new(Game::new(Some(1)), players);
+        gm.update_hand_strength(&Range::full()).unwrap();
+        assert!(gm.players.iter().all(|p| p.hand_equity == 0.0));
+
+        let board = cards("2c3d4h5s9c");
+        gm.gamestate.table_cards = Some(board.clone());
+        gm.update_hand_strength(&Range::full()).unwrap();
+        let expected = hand_strength(&cards("QsQd"), &board, &Range::full(), 1).unwrap().ehs;
+        assert_eq!(gm.players[1].hand_equity, expected);
+    }
+}
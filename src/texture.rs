@@ -0,0 +1,212 @@
+use serde::{Deserialize, Serialize};
+
+use crate::cardset::CardSet;
+use crate::{Card, Game, HandError, Suit, Variant};
+
+// board texture for the bot and coaching tools, works on a flop, turn or river
+// scores run from 0 to 1 so boards of different sizes can be compared
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SuitTexture {
+    // every card a different suit
+    Rainbow,
+    // at least two cards share a suit but not all of them
+    TwoTone,
+    Monotone,
+}
+
+/// the strongest rank pattern on the board itself
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Pairing {
+    Unpaired,
+    Paired,
+    TwoPaired,
+    Trips,
+    FullHouse,
+    Quads,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Connectedness {
+    // some two hole cards complete a straight
+    Connected,
+    Disconnected,
+}
+
+/// a straight two hole cards (or fewer) can make on this board
+/// needs holds the rank values the hole cards have to supply, empty when the board is the straight
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PossibleStraight {
+    pub high: u8,
+    pub needs: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BoardTexture {
+    pub suits: SuitTexture,
+    pub pairing: Pairing,
+    pub connectedness: Connectedness,
+    // rank value of the top card, 14 for an ace
+    pub high_card: u8,
+    pub high_card_score: f64,
+    pub wetness: f64,
+    // suits with three or more cards out, a flush is already possible in them
+    pub flush_suits: Vec<Suit>,
+    // suits with two cards out while more cards are to come
+    pub flush_draw_suits: Vec<Suit>,
+    // best straight first
+    pub straights: Vec<PossibleStraight>,
+}
+
+impl BoardTexture {
+    pub fn flush_possible(&self) -> bool {
+        !self.flush_suits.is_empty()
+    }
+
+    pub fn straight_possible(&self) -> bool {
+        !self.straights.is_empty()
+    }
+
+    pub fn is_paired(&self) -> bool {
+        self.pairing != Pairing::Unpaired
+    }
+}
+
+pub fn board_texture(board: &[Card]) -> Result<BoardTexture, HandError> {
+    board_texture_with(board, Variant::Holdem)
+}
+
+pub fn board_texture_with(board: &[Card], variant: Variant) -> Result<BoardTexture, HandError> {
+    let set = CardSet::from(board);
+    if set.len() != board.len() || set.len() < 3 || set.len() > 5 {
+        return Err(HandError::InvalidSize(set.len()));
+    }
+    if let Some(c) = board.iter().find(|c| !variant.uses_rank(c.rank)) {
+        return Err(HandError::CardNotInVariant(*c));
+    }
+    let to_come = set.len() < 5;
+
+    // suits
+    let suit_counts: Vec<(Suit, u32)> = Suit::ALL.iter().map(|s| (*s, set.suit_mask(s).count_ones())).collect();
+    let most_suited = suit_counts.iter().map(|(_, n)| *n).max().unwrap_or(0);
+    let suits = if most_suited as usize == set.len() {
+        SuitTexture::Monotone
+    } else if most_suited >= 2 {
+        SuitTexture::TwoTone
+    } else {
+        SuitTexture::Rainbow
+    };
+    let flush_suits: Vec<Suit> = suit_counts.iter().filter(|(_, n)| *n >= 3).map(|(s, _)| *s).collect();
+    let flush_draw_suits: Vec<Suit> = suit_counts
+        .iter()
+        .filter(|(_, n)| to_come && *n == 2)
+        .map(|(s, _)| *s)
+        .collect();
+
+    // ranks
+    let mut counts = [0u8; 15];
+    for c in board {
+        counts[c.rank.high_value() as usize] += 1;
+    }
+    let groups = |n: u8| counts.iter().filter(|c| **c == n).count();
+    let pairing = match (groups(4), groups(3), groups(2)) {
+        (1, _, _) => Pairing::Quads,
+        (_, 1, 1) => Pairing::FullHouse,
+        (_, 1, _) => Pairing::Trips,
+        (_, _, 2) => Pairing::TwoPaired,
+        (_, _, 1) => Pairing::Paired,
+        _ => Pairing::Unpaired,
+    };
+
+    let straights = possible_straights(set.rank_mask(), variant);
+    let connectedness = if straights.is_empty() { Connectedness::Disconnected } else { Connectedness::Connected };
+
+    let high_card = board.iter().map(|c| c.rank.high_value()).max().unwrap_or(0);
+    let lowest = if variant == Variant::ShortDeck { 6 } else { 2 };
+    let high_card_score = (high_card - lowest) as f64 / (14 - lowest) as f64;
+
+    // half from flushes and half from straights, paired boards give less away to draws
+    let flush_part = match most_suited {
+        0 | 1 => 0.0,
+        2 if to_come => 0.5,
+        2 => 0.0,
+        _ => 1.0,
+    };
+    let straight_part = (straights.len() as f64 / 3.0).min(1.0);
+    let pairing_damp = match pairing {
+        Pairing::Unpaired => 1.0,
+        Pairing::Paired => 0.85,
+        _ => 0.7,
+    };
+    let wetness = (flush_part + straight_part) / 2.0 * pairing_damp;
+
+    Ok(BoardTexture {
+        suits,
+        pairing,
+        connectedness,
+        high_card,
+        high_card_score,
+        wetness,
+        flush_suits,
+        flush_draw_suits,
+        straights,
+    })
+}
+
+// (high card, rank mask) of every straight in the variant, best first and the wheel last
+pub(crate) fn straight_windows(variant: Variant) -> Vec<(u8, u16)> {
+    let lowest_high = if variant == Variant::ShortDeck { 10 } else { 6 };
+    let mut windows: Vec<(u8, u16)> = (lowest_high..=14u8).rev().map(|high| (high, 0b11111 << (high - 6))).collect();
+    match variant {
+        Variant::Holdem => windows.push((5, 0b1_0000_0000_1111)),
+        Variant::ShortDeck => windows.push((9, 0b1_0000_1111_0000)),
+    }
+    windows
+}
+
+// every straight window already holding at least three board ranks
+fn possible_straights(mask: u16, variant: Variant) -> Vec<PossibleStraight> {
+    straight_windows(variant)
+        .into_iter()
+        .filter_map(|(high, window)| {
+            let missing = window & !mask;
+            let needs: Vec<u8> = (0..13u8).rev().filter(|i| missing & (1 << i) != 0).map(|i| i + 2).collect();
+            (needs.len() <= 2).then_some(PossibleStraight { high, needs })
+        })
+        .collect()
+}
+
+impl Game {
+    /// texture of the cards on the table, None before the flop
+    pub fn board_texture(&self) -> Option<BoardTexture> {
+        let table = self.table_cards.as_ref()?;
+        board_texture_with(table, self.variant).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Hand;
+
+    fn board(s: &str) -> Vec<Card> {
+        s.parse::<Hand>().unwrap().cards
+    }
+
+    #[test]
+    fn short_deck_rejects_cards_below_six() {
+        let low = board("Ah5c9d");
+        assert!(board_texture(&low).is_ok());
+        assert!(matches!(
+            board_texture_with(&low, Variant::ShortDeck),
+            Err(HandError::CardNotInVariant(c)) if c.as_string() == "5c"
+        ));
+    }
+
+    #[test]
+    fn short_deck_high_card_score_starts_at_six() {
+        let texture = board_texture_with(&board("6c7d9h"), Variant::ShortDeck).unwrap();
+        assert_eq!(texture.high_card, 9);
+        assert_eq!(texture.high_card_score, 3.0 / 8.0);
+    }
+}